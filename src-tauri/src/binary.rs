@@ -0,0 +1,566 @@
+//! Identification of binary files from their leading bytes (magic numbers), with basic metadata:
+//! image dimensions, PDF page count, SQLite tables, archive entries.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+/// What a binary file was identified as.
+#[derive(Debug, Clone, Serialize)]
+pub struct BinaryInfo {
+    /// Short kind, e.g. `png`, `pdf`, `sqlite`, `xlsx`.
+    pub kind: String,
+    /// Human-readable description, e.g. `ELF 64-bit LSB shared object, x86-64`.
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pages: Option<u32>,
+    /// SQLite tables and views.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tables: Option<Vec<String>>,
+    /// Archive members, the original name of a gzip file, or the files of a descriptor set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entries: Option<Vec<String>>,
+}
+
+impl BinaryInfo {
+    fn new(kind: &str, description: &str) -> Self {
+        BinaryInfo {
+            kind: kind.to_string(),
+            description: description.to_string(),
+            version: None,
+            width: None,
+            height: None,
+            pages: None,
+            tables: None,
+            entries: None,
+        }
+    }
+
+    fn size(mut self, dims: Option<(u32, u32)>) -> Self {
+        if let Some((w, h)) = dims {
+            self.width = Some(w);
+            self.height = Some(h);
+        }
+        self
+    }
+}
+
+/// Signatures with no metadata beyond the kind.
+const SIGNATURES: &[(&[u8], &str, &str)] = &[
+    (b"ARROW1", "arrow", "Arrow IPC file"),
+    (b"\0asm", "wasm", "WebAssembly module"),
+    (b"BZh", "bzip2", "bzip2 compressed data"),
+    (b"\xfd7zXZ\0", "xz", "xz compressed data"),
+    (b"\x28\xb5\x2f\xfd", "zstd", "Zstandard compressed data"),
+    (b"7z\xbc\xaf\x27\x1c", "7z", "7-Zip archive"),
+    (b"Rar!\x1a\x07", "rar", "RAR archive"),
+    (
+        b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1",
+        "ole",
+        "OLE2 compound document (.xls, .doc, .msi)",
+    ),
+    (b"OggS", "ogg", "Ogg media"),
+    (b"fLaC", "flac", "FLAC audio"),
+    (b"ID3", "mp3", "MP3 audio"),
+];
+
+fn be16(b: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(b.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn be32(b: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(b.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn le16(b: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(b.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn le32(b: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(b.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// Width and height from the first start-of-frame marker.
+fn jpeg_size(b: &[u8]) -> Option<(u32, u32)> {
+    let mut i = 2;
+    while i + 9 < b.len() {
+        if b[i] != 0xff {
+            return None;
+        }
+        let marker = b[i + 1];
+        let len = be16(b, i + 2)? as usize;
+        if (0xc0..=0xcf).contains(&marker) && !matches!(marker, 0xc4 | 0xc8 | 0xcc) {
+            return Some((be16(b, i + 7)? as u32, be16(b, i + 5)? as u32));
+        }
+        i += 2 + len;
+    }
+    None
+}
+
+/// Count `/Type /Page` objects; fall back to the largest `/Count` of a page tree when pages
+/// live in compressed object streams.
+fn pdf_pages(b: &[u8]) -> Option<u32> {
+    let text = String::from_utf8_lossy(b);
+    let mut pages = 0;
+    for (i, _) in text.match_indices("/Type") {
+        let rest = text[i + 5..].trim_start();
+        if rest.starts_with("/Page") && !rest[5..].starts_with(|c: char| c.is_alphanumeric()) {
+            pages += 1;
+        }
+    }
+    if pages > 0 {
+        return Some(pages);
+    }
+    text.match_indices("/Count")
+        .filter_map(|(i, _)| {
+            let rest = text[i + 6..].trim_start();
+            rest[..rest.find(|c: char| !c.is_ascii_digit())?]
+                .parse()
+                .ok()
+        })
+        .max()
+}
+
+/// Member names from the central directory.
+fn zip_entries(b: &[u8]) -> Option<Vec<String>> {
+    let eocd = (0..b.len().saturating_sub(21))
+        .rev()
+        .find(|&i| b[i..].starts_with(b"PK\x05\x06"))?;
+    let count = le16(b, eocd + 10)? as usize;
+    let mut at = le32(b, eocd + 16)? as usize;
+    let mut entries = Vec::with_capacity(count);
+    for _ in 0..count {
+        if !b.get(at..)?.starts_with(b"PK\x01\x02") {
+            break;
+        }
+        let name_len = le16(b, at + 28)? as usize;
+        let extra_len = le16(b, at + 30)? as usize;
+        let comment_len = le16(b, at + 32)? as usize;
+        entries.push(String::from_utf8_lossy(b.get(at + 46..at + 46 + name_len)?).into_owned());
+        at += 46 + name_len + extra_len + comment_len;
+    }
+    Some(entries)
+}
+
+/// Member names from the local headers, for the start of an archive whose central directory wasn't
+/// read. Stops at a member whose size only follows its data.
+fn zip_local_entries(b: &[u8]) -> Vec<String> {
+    let mut entries = Vec::new();
+    let mut at = 0;
+    while b
+        .get(at..)
+        .is_some_and(|rest| rest.starts_with(b"PK\x03\x04"))
+    {
+        let (Some(flags), Some(size), Some(name_len), Some(extra_len)) = (
+            le16(b, at + 6),
+            le32(b, at + 18),
+            le16(b, at + 26),
+            le16(b, at + 28),
+        ) else {
+            break;
+        };
+        let Some(name) = b.get(at + 30..at + 30 + name_len as usize) else {
+            break;
+        };
+        entries.push(String::from_utf8_lossy(name).into_owned());
+        if flags & 0x08 != 0 {
+            break;
+        }
+        at += 30 + name_len as usize + extra_len as usize + size as usize;
+    }
+    entries
+}
+
+fn zip(b: &[u8]) -> BinaryInfo {
+    let entries = zip_entries(b).unwrap_or_else(|| zip_local_entries(b));
+    let has = |name: &str| entries.iter().any(|e| e == name);
+    // An OpenDocument file stores its mimetype uncompressed as the first member.
+    let odf = b.get(30..38) == Some(&b"mimetype"[..])
+        && b.get(38..)
+            .is_some_and(|m| m.starts_with(b"application/vnd.oasis.opendocument."));
+    let (kind, description) = if has("xl/workbook.xml") {
+        ("xlsx", "Excel workbook (.xlsx)")
+    } else if has("word/document.xml") {
+        ("docx", "Word document (.docx)")
+    } else if has("ppt/presentation.xml") {
+        ("pptx", "PowerPoint presentation (.pptx)")
+    } else if odf && b[38..].starts_with(b"application/vnd.oasis.opendocument.spreadsheet") {
+        ("ods", "OpenDocument spreadsheet (.ods)")
+    } else if odf {
+        ("odf", "OpenDocument file")
+    } else if has("META-INF/MANIFEST.MF") {
+        ("jar", "Java archive (.jar)")
+    } else {
+        ("zip", "ZIP archive")
+    };
+    let mut info = BinaryInfo::new(kind, description);
+    info.entries = Some(entries);
+    info
+}
+
+/// Original file name from the gzip header, when stored.
+fn gzip_name(b: &[u8]) -> Option<String> {
+    let flags = *b.get(3)?;
+    if flags & 0x08 == 0 {
+        return None;
+    }
+    let mut at = 10;
+    if flags & 0x04 != 0 {
+        at += 2 + le16(b, 10)? as usize;
+    }
+    let len = b.get(at..)?.iter().position(|&c| c == 0)?;
+    Some(String::from_utf8_lossy(&b[at..at + len]).into_owned())
+}
+
+fn elf(b: &[u8]) -> Option<BinaryInfo> {
+    let bits = match b.get(4)? {
+        1 => "32-bit",
+        2 => "64-bit",
+        _ => return None,
+    };
+    let le = *b.get(5)? == 1;
+    let half = |at| if le { le16(b, at) } else { be16(b, at) };
+    let file_type = match half(16)? {
+        1 => "relocatable",
+        2 => "executable",
+        3 => "shared object",
+        4 => "core file",
+        _ => "file",
+    };
+    let machine = match half(18)? {
+        0x03 => "x86",
+        0x3e => "x86-64",
+        0x28 => "ARM",
+        0xb7 => "AArch64",
+        0xf3 => "RISC-V",
+        0x08 => "MIPS",
+        0x14 => "PowerPC",
+        0x15 => "PowerPC64",
+        _ => "unknown machine",
+    };
+    let description = format!(
+        "ELF {} {} {}, {}",
+        bits,
+        if le { "LSB" } else { "MSB" },
+        file_type,
+        machine
+    );
+    Some(BinaryInfo::new("elf", &description))
+}
+
+/// SQLite variable-length integer: returns the value and its length.
+fn sqlite_varint(b: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for (i, &byte) in b.iter().take(9).enumerate() {
+        if i == 8 {
+            return Some(((value << 8) | byte as u64, 9));
+        }
+        value = (value << 7) | (byte & 0x7f) as u64;
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+/// Text columns of a record, `None` for columns of other types.
+fn sqlite_record(payload: &[u8]) -> Option<Vec<Option<String>>> {
+    let (header_len, mut at) = sqlite_varint(payload)?;
+    let mut body = header_len as usize;
+    let mut columns = Vec::new();
+    while at < header_len as usize {
+        let (serial, n) = sqlite_varint(payload.get(at..)?)?;
+        at += n;
+        let size = match serial {
+            0 | 8 | 9 => 0,
+            1..=4 => serial as usize,
+            5 => 6,
+            6 | 7 => 8,
+            s if s >= 12 => (s as usize - 12) / 2,
+            _ => return None,
+        };
+        let text = (serial >= 13 && serial % 2 == 1)
+            .then(|| {
+                payload
+                    .get(body..body + size)
+                    .map(|t| String::from_utf8_lossy(t).into_owned())
+            })
+            .flatten();
+        columns.push(text);
+        body += size;
+    }
+    Some(columns)
+}
+
+/// Names of the tables and views in `sqlite_schema`, the b-tree rooted on page 1. Each page is
+/// read at most once, so a corrupt tree whose pointers form a cycle still ends.
+fn sqlite_tables(b: &[u8]) -> Option<Vec<String>> {
+    let page_size = match be16(b, 16)? {
+        1 => 65536,
+        n => n as usize,
+    };
+    let mut names = Vec::new();
+    let mut pages = vec![1u32];
+    let mut visited = HashSet::new();
+    while let Some(page) = pages.pop() {
+        if names.len() > 10_000 || page == 0 {
+            break;
+        }
+        if !visited.insert(page) {
+            continue;
+        }
+        let start = (page as usize - 1) * page_size;
+        let header = if page == 1 { 100 } else { start };
+        let kind = *b.get(header)?;
+        let cells = be16(b, header + 3)? as usize;
+        let pointers = header + if kind == 0x05 { 12 } else { 8 };
+        if kind == 0x05 {
+            pages.push(be32(b, header + 8)?);
+        } else if kind != 0x0d {
+            return None;
+        }
+        for c in 0..cells {
+            let cell = start + be16(b, pointers + 2 * c)? as usize;
+            if kind == 0x05 {
+                pages.push(be32(b, cell)?);
+                continue;
+            }
+            let (_, n) = sqlite_varint(b.get(cell..)?)?;
+            let (_, m) = sqlite_varint(b.get(cell + n..)?)?;
+            let payload = b.get(cell + n + m..(start + page_size).min(b.len()))?;
+            if let Some(columns) = sqlite_record(payload) {
+                let kind = columns.first().cloned().flatten();
+                let name = columns.get(1).cloned().flatten();
+                if let (Some("table" | "view"), Some(name)) = (kind.as_deref(), name) {
+                    if !name.starts_with("sqlite_") {
+                        names.push(name);
+                    }
+                }
+            }
+        }
+    }
+    Some(names)
+}
+
+/// Protobuf varint: returns the value and its length.
+fn varint(b: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for (i, &byte) in b.iter().take(10).enumerate() {
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+/// File names of a serialized `FileDescriptorSet`: every top-level field is `file` (1, length
+/// delimited) and each file starts with its `name` (1, string) ending in `.proto`.
+fn descriptor_set_files(b: &[u8]) -> Option<Vec<String>> {
+    let mut files = Vec::new();
+    let mut at = 0;
+    while at < b.len() {
+        if b[at] != 0x0a {
+            return None;
+        }
+        let (len, n) = varint(&b[at + 1..])?;
+        let file = b.get(at + 1 + n..at + 1 + n + len as usize)?;
+        if file.first() != Some(&0x0a) {
+            return None;
+        }
+        let (name_len, m) = varint(&file[1..])?;
+        let name = std::str::from_utf8(file.get(1 + m..1 + m + name_len as usize)?).ok()?;
+        if !name.ends_with(".proto") {
+            return None;
+        }
+        files.push(name.to_string());
+        at += 1 + n + len as usize;
+    }
+    (!files.is_empty()).then_some(files)
+}
+
+/// Identify a binary format from the file's bytes. Metadata needing the whole file (PDF pages,
+/// ZIP entries, Parquet footer) is only found when the bytes are complete.
+pub fn identify(b: &[u8]) -> Option<BinaryInfo> {
+    if b.starts_with(b"\x89PNG\r\n\x1a\n") {
+        let dims = be32(b, 16).zip(be32(b, 20));
+        return Some(BinaryInfo::new("png", "PNG image").size(dims));
+    }
+    if b.starts_with(b"\xff\xd8\xff") {
+        return Some(BinaryInfo::new("jpeg", "JPEG image").size(jpeg_size(b)));
+    }
+    if b.starts_with(b"GIF87a") || b.starts_with(b"GIF89a") {
+        let dims = le16(b, 6)
+            .zip(le16(b, 8))
+            .map(|(w, h)| (w as u32, h as u32));
+        return Some(BinaryInfo::new("gif", "GIF image").size(dims));
+    }
+    if b.starts_with(b"RIFF") && b.get(8..12) == Some(&b"WEBP"[..]) {
+        return Some(BinaryInfo::new("webp", "WebP image"));
+    }
+    if b.starts_with(b"%PDF-") {
+        let mut info = BinaryInfo::new("pdf", "PDF document");
+        info.version = b.get(5..8).map(|v| String::from_utf8_lossy(v).into_owned());
+        info.pages = pdf_pages(b);
+        return Some(info);
+    }
+    if b.starts_with(b"PK\x03\x04") || b.starts_with(b"PK\x05\x06") {
+        return Some(zip(b));
+    }
+    if b.starts_with(b"\x1f\x8b\x08") {
+        let mut info = BinaryInfo::new("gzip", "gzip compressed data");
+        info.entries = gzip_name(b).map(|name| vec![name]);
+        return Some(info);
+    }
+    if b.starts_with(b"SQLite format 3\0") {
+        let mut info = BinaryInfo::new("sqlite", "SQLite 3 database");
+        info.tables = sqlite_tables(b);
+        return Some(info);
+    }
+    if b.starts_with(b"\x7fELF") {
+        return elf(b);
+    }
+    if b.starts_with(b"PAR1") {
+        return Some(BinaryInfo::new("parquet", "Apache Parquet file"));
+    }
+    if let Some(&(_, kind, description)) =
+        SIGNATURES.iter().find(|(magic, _, _)| b.starts_with(magic))
+    {
+        return Some(BinaryInfo::new(kind, description));
+    }
+    if let Some(format) = crate::serialized::sniff(b) {
+        let description = match format {
+            "msgpack" => "MessagePack data",
+            "cbor" => "CBOR data",
+            _ => "BSON documents",
+        };
+        return Some(BinaryInfo::new(format, description));
+    }
+    descriptor_set_files(b).map(|files| {
+        let mut info =
+            BinaryInfo::new("protobuf-descriptor-set", "Protocol Buffers descriptor set");
+        info.entries = Some(files);
+        info
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identify_reads_image_dimensions_and_elf_header() {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend([0, 0, 3, 0x20, 0, 0, 2, 0x58]);
+        let info = identify(&png).unwrap();
+        assert_eq!(
+            (info.kind.as_str(), info.width, info.height),
+            ("png", Some(800), Some(600))
+        );
+        let jpeg = b"\xff\xd8\xff\xe0\x00\x04\x00\x00\xff\xc0\x00\x11\x08\x01\xe0\x02\x80\x03";
+        let info = identify(jpeg).unwrap();
+        assert_eq!((info.width, info.height), (Some(640), Some(480)));
+        let mut elf = b"\x7fELF\x02\x01\x01".to_vec();
+        elf.resize(16, 0);
+        elf.extend([3, 0, 0x3e, 0]);
+        assert_eq!(
+            identify(&elf).unwrap().description,
+            "ELF 64-bit LSB shared object, x86-64"
+        );
+        assert!(identify(b"plain text").is_none());
+    }
+
+    #[test]
+    fn identify_lists_zip_entries_and_pdf_pages() {
+        let mut zip = b"PK\x03\x04".to_vec();
+        zip.resize(30, 0);
+        let cd = zip.len() as u32;
+        for name in ["[Content_Types].xml", "xl/workbook.xml"] {
+            let mut entry = b"PK\x01\x02".to_vec();
+            entry.resize(28, 0);
+            entry.extend((name.len() as u16).to_le_bytes());
+            entry.resize(46, 0);
+            entry.extend(name.as_bytes());
+            zip.extend(entry);
+        }
+        let cd_len = zip.len() as u32 - cd;
+        zip.extend(b"PK\x05\x06\0\0\0\0\x02\0\x02\0");
+        zip.extend(cd_len.to_le_bytes());
+        zip.extend(cd.to_le_bytes());
+        zip.extend([0, 0]);
+        let info = identify(&zip).unwrap();
+        assert_eq!(info.kind, "xlsx");
+        assert_eq!(
+            info.entries.unwrap(),
+            vec!["[Content_Types].xml", "xl/workbook.xml"]
+        );
+        // Only the start of the archive: the kind comes from the local headers.
+        let mut prefix = Vec::new();
+        for (name, data) in [
+            ("[Content_Types].xml", &b"<Types/>"[..]),
+            ("xl/workbook.xml", b""),
+        ] {
+            let mut header = b"PK\x03\x04".to_vec();
+            header.resize(18, 0);
+            header.extend((data.len() as u32).to_le_bytes());
+            header.resize(26, 0);
+            header.extend((name.len() as u16).to_le_bytes());
+            header.extend([0, 0]);
+            header.extend(name.as_bytes());
+            prefix.extend(header);
+            prefix.extend(data);
+        }
+        assert_eq!(identify(&prefix).unwrap().kind, "xlsx");
+        let pdf = b"%PDF-1.7\n1 0 obj << /Type /Pages /Count 2 >>\n2 0 obj << /Type /Page >>\n3 0 obj <</Type/Page>>";
+        let info = identify(pdf).unwrap();
+        assert_eq!(
+            (info.version.as_deref(), info.pages),
+            (Some("1.7"), Some(2))
+        );
+    }
+
+    #[test]
+    fn identify_lists_sqlite_tables_and_descriptor_files() {
+        // Page 1 of a database holding one table: header, leaf b-tree page with one cell.
+        let record = |values: &[&str]| {
+            let mut header = vec![values.len() as u8 + 1];
+            header.extend(values.iter().map(|v| (v.len() * 2 + 13) as u8));
+            [header, values.concat().into_bytes()].concat()
+        };
+        let payload = record(&["table", "users", "users", "CREATE TABLE users(id)"]);
+        let mut db = b"SQLite format 3\0\x02\0".to_vec();
+        db.resize(100, 0);
+        let cell = 512 - payload.len() - 2;
+        db.extend([0x0d, 0, 0, 0, 1]);
+        db.extend((cell as u16).to_be_bytes());
+        db.extend([
+            0,
+            (cell as u16).to_be_bytes()[0],
+            (cell as u16).to_be_bytes()[1],
+        ]);
+        db.resize(cell, 0);
+        db.extend([payload.len() as u8, 1]);
+        db.extend(payload);
+        let info = identify(&db).unwrap();
+        assert_eq!(info.tables.unwrap(), vec!["users"]);
+        // An interior page 1 whose right-most child is page 1 itself.
+        let mut looped = b"SQLite format 3\0\x02\0".to_vec();
+        looped.resize(100, 0);
+        looped.extend([0x05, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+        looped.resize(512, 0);
+        assert_eq!(
+            identify(&looped).unwrap().tables.unwrap(),
+            Vec::<String>::new()
+        );
+        let set = b"\x0a\x0c\x0a\x0auser.proto\x0a\x0e\x0a\x0corders.proto";
+        let info = identify(set).unwrap();
+        assert_eq!(info.kind, "protobuf-descriptor-set");
+        assert_eq!(info.entries.unwrap(), vec!["user.proto", "orders.proto"]);
+    }
+}
@@ -0,0 +1,175 @@
+//! CSV dialect sniffing: delimiter, quote character, escape style and header row.
+
+use serde::{Deserialize, Serialize};
+
+/// Delimiters we try, in order of preference when several fit equally well.
+const CANDIDATE_DELIMITERS: [char; 4] = [',', '\t', ';', '|'];
+
+/// How a delimited file is written, as inferred from its content.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CsvDialect {
+    pub delimiter: char,
+    pub quote: char,
+    /// True when quotes inside quoted fields are escaped with a backslash instead of doubled.
+    pub backslash_escape: bool,
+    pub has_header: bool,
+}
+
+/// Split one line into fields, honouring quotes. Quoted fields spanning lines are not joined.
+fn split_line(line: &str, delimiter: char, quote: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' && in_quotes && chars.peek() == Some(&quote) {
+            field.push(quote);
+            chars.next();
+        } else if c == quote {
+            if in_quotes && chars.peek() == Some(&quote) {
+                field.push(quote);
+                chars.next();
+            } else {
+                in_quotes = !in_quotes;
+            }
+        } else if c == delimiter && !in_quotes {
+            fields.push(std::mem::take(&mut field));
+        } else {
+            field.push(c);
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+fn data_lines(content: &str) -> Vec<&str> {
+    content.lines().filter(|l| !l.trim().is_empty()).collect()
+}
+
+/// Pick the quote character: single quotes only when fields start with them more often than with double quotes.
+fn sniff_quote(content: &str, delimiter: char) -> char {
+    let starts_with = |q: char| {
+        data_lines(content)
+            .iter()
+            .flat_map(|l| l.split(delimiter))
+            .filter(|f| f.trim_start().starts_with(q))
+            .count()
+    };
+    if starts_with('\'') > starts_with('"') {
+        '\''
+    } else {
+        '"'
+    }
+}
+
+/// A delimiter that splits every line into the same number (at least two) of fields.
+/// When several do, the one producing the most fields wins.
+fn sniff_delimiter(content: &str) -> Option<char> {
+    let lines = data_lines(content);
+    if lines.len() < 2 {
+        return None;
+    }
+    let mut best: Option<(char, usize)> = None;
+    for delimiter in CANDIDATE_DELIMITERS {
+        let quote = sniff_quote(content, delimiter);
+        let counts: Vec<usize> = lines
+            .iter()
+            .map(|l| split_line(l, delimiter, quote).len())
+            .collect();
+        let consistent = counts[0] >= 2 && counts.iter().all(|&c| c == counts[0]);
+        if consistent && best.is_none_or(|(_, n)| counts[0] > n) {
+            best = Some((delimiter, counts[0]));
+        }
+    }
+    best.map(|(d, _)| d)
+}
+
+fn is_numeric(cell: &str) -> bool {
+    let t = cell.trim();
+    !t.is_empty() && t.parse::<f64>().is_ok()
+}
+
+/// A header is assumed when some column is numeric in every data row but not in the first row.
+fn sniff_header(rows: &[Vec<String>]) -> bool {
+    let Some((first, rest)) = rows.split_first() else {
+        return false;
+    };
+    if rest.is_empty() {
+        return false;
+    }
+    first.iter().enumerate().any(|(c, head)| {
+        !is_numeric(head)
+            && rest
+                .iter()
+                .all(|row| row.get(c).is_some_and(|cell| is_numeric(cell)))
+    })
+}
+
+/// True if the content looks like delimited data: a consistent delimiter, or (as before) a comma on the first of several lines.
+pub fn looks_like_csv(content: &str) -> bool {
+    if sniff_delimiter(content).is_some() {
+        return true;
+    }
+    content.contains('\n') && content.lines().next().unwrap_or("").contains(',')
+}
+
+/// Infer the dialect of delimited content. `.tsv` / `.tab` extensions force a tab delimiter;
+/// otherwise a comma is assumed when no delimiter fits consistently.
+pub fn sniff(content: &str, ext: &str) -> CsvDialect {
+    let delimiter = match ext.to_lowercase().as_str() {
+        "tsv" | "tab" => '\t',
+        _ => sniff_delimiter(content).unwrap_or(','),
+    };
+    let quote = sniff_quote(content, delimiter);
+    let backslash_escape = content.contains(&format!("\\{}", quote));
+    let rows: Vec<Vec<String>> = data_lines(content)
+        .iter()
+        .take(50)
+        .map(|l| split_line(l, delimiter, quote))
+        .collect();
+    CsvDialect {
+        delimiter,
+        quote,
+        backslash_escape,
+        has_header: sniff_header(&rows),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sniff_detects_delimiters() {
+        assert_eq!(sniff("a,b,c\n1,2,3", "").delimiter, ',');
+        assert_eq!(sniff("a\tb\n1\t2", "").delimiter, '\t');
+        assert_eq!(sniff("name;price\nfoo;1,5\nbar;2,25", "").delimiter, ';');
+        assert_eq!(sniff("id|name\n1|x\n2|y", "").delimiter, '|');
+        assert_eq!(sniff("single column", "tsv").delimiter, '\t');
+    }
+
+    #[test]
+    fn sniff_detects_quote_and_escape_style() {
+        let d = sniff("'a';'b'\n'x';'it\\'s'", "");
+        assert_eq!(d.delimiter, ';');
+        assert_eq!(d.quote, '\'');
+        assert!(d.backslash_escape);
+        let d = sniff("\"a\",\"b\"\n\"say \"\"hi\"\"\",2", "");
+        assert_eq!(d.quote, '"');
+        assert!(!d.backslash_escape);
+    }
+
+    #[test]
+    fn sniff_detects_header_row() {
+        assert!(sniff("name,age\nAlice,30\nBob,41", "").has_header);
+        assert!(!sniff("1,2\n3,4", "").has_header);
+        assert!(!sniff("a,b\nc,d", "").has_header);
+    }
+
+    #[test]
+    fn looks_like_csv_ignores_plain_text() {
+        assert!(looks_like_csv("a\tb\n1\t2"));
+        assert!(!looks_like_csv("plain text\nno structure"));
+        assert!(!looks_like_csv("one; two\nthree"));
+    }
+}
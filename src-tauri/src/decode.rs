@@ -0,0 +1,306 @@
+//! Encoded-value inspector: tries base64/base64url, hex, percent-encoding, gzip/zlib and JWT on a
+//! selected value, following chains of encodings until the result is plain text or JSON.
+
+use std::io::Read;
+
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::DecodePaddingMode;
+use base64::Engine;
+use chrono::{DateTime, SecondsFormat, Utc};
+use flate2::read::{GzDecoder, ZlibDecoder};
+use serde::Serialize;
+
+use crate::{content_detection_heuristic, format_json};
+
+const PADDING_OPTIONAL: GeneralPurposeConfig =
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+const BASE64: GeneralPurpose = GeneralPurpose::new(&base64::alphabet::STANDARD, PADDING_OPTIONAL);
+const BASE64_URL: GeneralPurpose =
+    GeneralPurpose::new(&base64::alphabet::URL_SAFE, PADDING_OPTIONAL);
+
+/// Longest chain of decodings followed (e.g. url → base64 → gzip → json).
+const MAX_DEPTH: usize = 4;
+
+/// JWT claims holding NumericDate values.
+const TIME_CLAIMS: &[&str] = &["exp", "iat", "nbf", "auth_time"];
+
+/// A time claim of a JWT shown as a date.
+#[derive(Debug, Serialize)]
+pub struct ClaimDate {
+    pub claim: String,
+    pub timestamp: i64,
+    /// RFC 3339 in UTC.
+    pub utc: String,
+    /// For `exp`: whether the token has expired.
+    pub expired: Option<bool>,
+}
+
+/// One successful decoding of the inspected value.
+#[derive(Debug, Serialize)]
+pub struct Decoding {
+    /// Steps applied in order: `jwt`, `base64`, `base64url`, `hex`, `url`, `gzip`, `zlib`.
+    pub chain: Vec<String>,
+    /// Detected kind of the decoded value (`json`, `xml`, `text`, ...).
+    pub kind: String,
+    /// The decoded value; JSON is pretty-printed.
+    pub value: String,
+    /// Time claims of a JWT.
+    pub dates: Vec<ClaimDate>,
+}
+
+/// Decoded bytes as text, if they are UTF-8 without control characters (other than whitespace).
+pub(crate) fn as_text(bytes: &[u8]) -> Option<String> {
+    let text = std::str::from_utf8(bytes).ok()?;
+    let printable = !text.trim().is_empty()
+        && text
+            .chars()
+            .all(|c| !c.is_control() || matches!(c, '\n' | '\r' | '\t'));
+    printable.then(|| text.to_string())
+}
+
+/// Undo gzip or zlib compression if the bytes start with its header.
+pub(crate) fn decompress(bytes: &[u8]) -> Option<(&'static str, Vec<u8>)> {
+    let mut out = Vec::new();
+    match bytes {
+        [0x1f, 0x8b, ..] => {
+            GzDecoder::new(bytes).read_to_end(&mut out).ok()?;
+            Some(("gzip", out))
+        }
+        [0x78, 0x01 | 0x5e | 0x9c | 0xda, ..] => {
+            ZlibDecoder::new(bytes).read_to_end(&mut out).ok()?;
+            Some(("zlib", out))
+        }
+        _ => None,
+    }
+}
+
+pub(crate) fn base64(value: &str) -> Option<(&'static str, Vec<u8>)> {
+    let body = value.trim_end_matches('=');
+    if body.len() < 8 || body.contains('=') || value.len() - body.len() > 2 {
+        return None;
+    }
+    let url = body.contains(['-', '_']);
+    let alphabet_ok = body.chars().all(|c| {
+        c.is_ascii_alphanumeric()
+            || if url {
+                c == '-' || c == '_'
+            } else {
+                c == '+' || c == '/'
+            }
+    });
+    if !alphabet_ok {
+        return None;
+    }
+    if url {
+        Some(("base64url", BASE64_URL.decode(body).ok()?))
+    } else {
+        Some(("base64", BASE64.decode(body).ok()?))
+    }
+}
+
+/// Hex digits, optionally `0x`-prefixed or separated by spaces or colons (`de:ad:be:ef`).
+pub(crate) fn hex(value: &str) -> Option<Vec<u8>> {
+    let digits: String = value
+        .strip_prefix("0x")
+        .unwrap_or(value)
+        .chars()
+        .filter(|c| !matches!(c, ' ' | ':'))
+        .collect();
+    if digits.len() < 4
+        || !digits.len().is_multiple_of(2)
+        || !digits.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        return None;
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Percent-decoding, when the value has at least one `%XX` escape.
+fn percent(value: &str) -> Option<Vec<u8>> {
+    let b = value.as_bytes();
+    let escaped = |i: usize| {
+        b.get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok())
+    };
+    if !(0..b.len()).any(|i| b[i] == b'%' && escaped(i).is_some()) {
+        return None;
+    }
+    let mut out = Vec::with_capacity(b.len());
+    let mut i = 0;
+    while i < b.len() {
+        match escaped(i).filter(|_| b[i] == b'%') {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(b[i]);
+                i += 1;
+            }
+        }
+    }
+    Some(out)
+}
+
+fn claim_dates(payload: &serde_json::Value) -> Vec<ClaimDate> {
+    let now = Utc::now().timestamp();
+    TIME_CLAIMS
+        .iter()
+        .filter_map(|claim| {
+            let timestamp = payload.get(*claim)?.as_i64()?;
+            let date = DateTime::<Utc>::from_timestamp(timestamp, 0)?;
+            Some(ClaimDate {
+                claim: claim.to_string(),
+                timestamp,
+                utc: date.to_rfc3339_opts(SecondsFormat::Secs, true),
+                expired: (*claim == "exp").then_some(timestamp <= now),
+            })
+        })
+        .collect()
+}
+
+/// `header.payload.signature`, where the header is a JSON object naming an `alg`.
+fn jwt(value: &str) -> Option<Decoding> {
+    let parts: Vec<&str> = value.split('.').collect();
+    if parts.len() != 3 {
+        return None;
+    }
+    // Keep each part's text as well, so the token is shown with its keys and numbers as written.
+    let json = |part: &str| -> Option<(String, serde_json::Value)> {
+        let text = String::from_utf8(BASE64_URL.decode(part.trim_end_matches('=')).ok()?).ok()?;
+        let value = serde_json::from_str(&text).ok()?;
+        Some((text, value))
+    };
+    let (header, _) = json(parts[0]).filter(|(_, h)| h.get("alg").is_some())?;
+    let (payload, claims) = json(parts[1])?;
+    let dates = claim_dates(&claims);
+    let token = format!(
+        r#"{{"header":{},"payload":{},"signature":{}}}"#,
+        header,
+        payload,
+        serde_json::Value::from(parts[2])
+    );
+    Some(Decoding {
+        chain: vec!["jwt".to_string()],
+        kind: "json".to_string(),
+        value: format_json(token, None).unwrap_or_default(),
+        dates,
+    })
+}
+
+/// Record the decoded bytes (decompressing them first if they are gzip/zlib) and, unless they
+/// are JSON, keep following the chain.
+fn push(chain: Vec<String>, bytes: Vec<u8>, out: &mut Vec<Decoding>) {
+    let (chain, bytes) = match decompress(&bytes) {
+        Some((step, inflated)) => ([chain, vec![step.to_string()]].concat(), inflated),
+        None => (chain, bytes),
+    };
+    let Some(text) = as_text(&bytes) else {
+        return;
+    };
+    let trimmed = text.trim();
+    let is_json = (trimmed.starts_with('{') || trimmed.starts_with('['))
+        && serde_json::from_str::<serde_json::Value>(trimmed).is_ok();
+    let (kind, value) = if is_json {
+        (
+            "json",
+            format_json(trimmed.to_string(), None).unwrap_or(text.clone()),
+        )
+    } else {
+        (content_detection_heuristic(&text).0, text.clone())
+    };
+    let depth = chain.len();
+    out.push(Decoding {
+        chain: chain.clone(),
+        kind: kind.to_string(),
+        value,
+        dates: Vec::new(),
+    });
+    if !is_json && depth < MAX_DEPTH {
+        decode_into(trimmed, chain, out);
+    }
+}
+
+fn decode_into(value: &str, chain: Vec<String>, out: &mut Vec<Decoding>) {
+    let step = |name: &str| [chain.clone(), vec![name.to_string()]].concat();
+    if let Some(mut token) = jwt(value) {
+        token.chain = step("jwt");
+        out.push(token);
+        return;
+    }
+    if let Some(bytes) = percent(value) {
+        push(step("url"), bytes, out);
+    }
+    if let Some(bytes) = hex(value) {
+        push(step("hex"), bytes, out);
+    }
+    if let Some((name, bytes)) = base64(value) {
+        push(step(name), bytes, out);
+    }
+}
+
+/// Try to decode a selected value: JWT, base64/base64url, hex and percent-encoding, including
+/// compressed payloads and chains of encodings. Returns every decoding that produced text,
+/// with its detected kind; JWT time claims are also given as dates.
+#[tauri::command]
+pub fn decode_value(value: String) -> Vec<Decoding> {
+    let mut out = Vec::new();
+    decode_into(value.trim(), Vec::new(), &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn chains(decodings: &[Decoding]) -> Vec<(String, &str)> {
+        decodings
+            .iter()
+            .map(|d| (d.chain.join(" > "), d.kind.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn decode_value_reads_jwt_claims_as_dates() {
+        let header = BASE64_URL.encode(r#"{"alg":"HS256","typ":"JWT"}"#);
+        let payload = BASE64_URL.encode(r#"{"sub":"42","iat":1700000000,"exp":1700003600}"#);
+        let out = decode_value(format!("{}.{}.c2ln", header, payload));
+        assert_eq!(chains(&out), vec![("jwt".to_string(), "json")]);
+        assert!(out[0]
+            .value
+            .contains("\"payload\": {\n    \"sub\": \"42\",\n    \"iat\": 1700000000,"));
+        let exp = out[0].dates.iter().find(|d| d.claim == "exp").unwrap();
+        assert_eq!(exp.utc, "2023-11-14T23:13:20Z");
+        assert_eq!(exp.expired, Some(true));
+    }
+
+    #[test]
+    fn decode_value_follows_url_base64_gzip_json() {
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(br#"{"user":"ana"}"#).unwrap();
+        let encoded = BASE64
+            .encode(gz.finish().unwrap())
+            .replace('+', "%2B")
+            .replace('/', "%2F")
+            .replace('=', "%3D");
+        let out = decode_value(encoded);
+        let last = out.last().unwrap();
+        assert_eq!(last.chain, vec!["url", "base64", "gzip"]);
+        assert_eq!(last.kind, "json");
+        assert_eq!(last.value, "{\n  \"user\": \"ana\"\n}");
+    }
+
+    #[test]
+    fn decode_value_handles_hex_and_ignores_noise() {
+        let out = decode_value("68656c6c6f2c20776f726c64".to_string());
+        assert_eq!(chains(&out), vec![("hex".to_string(), "text")]);
+        assert_eq!(out[0].value, "hello, world");
+        assert!(decode_value("helloworld".to_string()).is_empty());
+        assert!(decode_value("just some words".to_string()).is_empty());
+    }
+}
@@ -0,0 +1,534 @@
+//! Email (RFC 5322 / MIME): header decoding, the multipart tree and decoded text parts.
+
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::DecodePaddingMode;
+use base64::Engine;
+use chrono::DateTime;
+use serde::Serialize;
+
+use crate::{content_detection_heuristic, http, Segment};
+
+/// Base64 as found in mail: padding is often missing from encoded words.
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &base64::alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Header fields of which a message header block has at least two.
+const MESSAGE_FIELDS: &[&str] = &[
+    "from",
+    "to",
+    "cc",
+    "subject",
+    "date",
+    "message-id",
+    "mime-version",
+    "received",
+    "return-path",
+    "delivered-to",
+    "reply-to",
+];
+
+/// A header field with its RFC 2047 encoded words decoded (1-based line of the field name).
+#[derive(Debug, Serialize)]
+pub struct EmailHeader {
+    pub name: String,
+    pub value: String,
+    pub line: u32,
+}
+
+/// A MIME entity: the message itself or one of its parts (1-based inclusive lines, headers included).
+#[derive(Debug, Serialize)]
+pub struct MimePart {
+    pub headers: Vec<EmailHeader>,
+    /// Lowercase media type; `text/plain` when there is no Content-Type.
+    pub content_type: String,
+    pub charset: Option<String>,
+    pub transfer_encoding: Option<String>,
+    /// Attachment file name from Content-Disposition or Content-Type.
+    pub filename: Option<String>,
+    pub start_line: u32,
+    pub end_line: u32,
+    /// 1-based line where the body starts, if there is a body.
+    pub body_line: Option<u32>,
+    /// Decoded text of a text part (transfer encoding and charset undone).
+    pub text: Option<String>,
+    /// Detected kind of `text`.
+    pub kind: Option<String>,
+    /// Size of the decoded body in bytes.
+    pub size: usize,
+    /// Set when the body could not be decoded.
+    pub error: Option<String>,
+    /// Children of a multipart entity, or the enclosed message of a `message/rfc822` part.
+    pub parts: Vec<MimePart>,
+}
+
+fn hex_byte(pair: &[u8]) -> Option<u8> {
+    let s = std::str::from_utf8(pair).ok()?;
+    u8::from_str_radix(s, 16).ok()
+}
+
+fn decode_charset(bytes: &[u8], charset: Option<&str>) -> String {
+    let encoding = charset
+        .and_then(|c| encoding_rs::Encoding::for_label(c.trim().as_bytes()))
+        .unwrap_or(encoding_rs::UTF_8);
+    encoding.decode(bytes).0.into_owned()
+}
+
+/// `Q` encoding of encoded words: quoted-printable with `_` for space.
+fn q_decode(text: &str) -> Vec<u8> {
+    let b = text.as_bytes();
+    let mut out = Vec::with_capacity(b.len());
+    let mut i = 0;
+    while i < b.len() {
+        match b[i] {
+            b'_' => out.push(b' '),
+            b'=' if b.get(i + 1..i + 3).and_then(hex_byte).is_some() => {
+                out.push(hex_byte(&b[i + 1..i + 3]).unwrap());
+                i += 2;
+            }
+            c => out.push(c),
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Quoted-printable body: `=XX` escapes, and `=` at the end of a line joins it to the next.
+fn quoted_printable(text: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(text.len());
+    let mut lines = text.lines().peekable();
+    while let Some(line) = lines.next() {
+        let line = line.trim_end_matches([' ', '\t']);
+        let (line, soft_break) = match line.strip_suffix('=') {
+            Some(l) => (l, true),
+            None => (line, false),
+        };
+        let b = line.as_bytes();
+        let mut i = 0;
+        while i < b.len() {
+            match b.get(i + 1..i + 3).and_then(hex_byte) {
+                Some(byte) if b[i] == b'=' => {
+                    out.push(byte);
+                    i += 3;
+                }
+                _ => {
+                    out.push(b[i]);
+                    i += 1;
+                }
+            }
+        }
+        if !soft_break && lines.peek().is_some() {
+            out.push(b'\n');
+        }
+    }
+    out
+}
+
+/// One `=?charset?B|Q?text?=` word at the start of `s`: decoded text and encoded length.
+fn encoded_word(s: &str) -> Option<(String, usize)> {
+    let inner = s.strip_prefix("=?")?;
+    let (charset, rest) = inner.split_once('?')?;
+    let (encoding, rest) = rest.split_once('?')?;
+    let end = rest.find("?=")?;
+    let text = &rest[..end];
+    if text.contains(char::is_whitespace) {
+        return None;
+    }
+    let bytes = match encoding {
+        "B" | "b" => BASE64.decode(text).ok()?,
+        "Q" | "q" => q_decode(text),
+        _ => return None,
+    };
+    // RFC 2231 allows a language after the charset: `utf-8*en`.
+    let label = charset.split('*').next().unwrap_or(charset);
+    let len = 2 + charset.len() + 1 + encoding.len() + 1 + end + 2;
+    Some((decode_charset(&bytes, Some(label)), len))
+}
+
+/// Decode RFC 2047 encoded words in a header value; whitespace between adjacent words is dropped.
+pub fn decode_header_value(value: &str) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    let mut after_word = false;
+    while let Some(start) = rest.find("=?") {
+        let (before, candidate) = rest.split_at(start);
+        match encoded_word(candidate) {
+            Some((text, len)) => {
+                if !(after_word && before.trim().is_empty()) {
+                    out.push_str(before);
+                }
+                out.push_str(&text);
+                rest = &candidate[len..];
+                after_word = true;
+            }
+            None => {
+                out.push_str(before);
+                out.push_str("=?");
+                rest = &candidate[2..];
+                after_word = false;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `text/plain; charset="utf-8"; format=flowed` → the value and its parameters (names lowercased).
+fn parameters(value: &str) -> (String, Vec<(String, String)>) {
+    let mut pieces: Vec<String> = vec![String::new()];
+    let mut quoted = false;
+    for c in value.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ';' if !quoted => pieces.push(String::new()),
+            c => pieces.last_mut().unwrap().push(c),
+        }
+    }
+    let main = pieces[0].trim().to_ascii_lowercase();
+    let params = pieces[1..]
+        .iter()
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| (k.trim().to_ascii_lowercase(), v.trim().to_string()))
+        .collect();
+    (main, params)
+}
+
+fn param<'a>(params: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    params
+        .iter()
+        .find(|(k, _)| k == name || k.strip_suffix('*') == Some(name))
+        .map(|(_, v)| v.as_str())
+}
+
+fn is_field_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_graphic() && c != ':')
+}
+
+/// Header fields from `lines[start..end]` (folded lines unfolded) and the index of the blank line
+/// that ends them (or of the first line that is not a field).
+fn header_block(lines: &[&str], start: usize, end: usize) -> (Vec<EmailHeader>, usize) {
+    let mut headers: Vec<EmailHeader> = Vec::new();
+    let mut i = start;
+    while i < end {
+        let line = lines[i];
+        if line.starts_with([' ', '\t']) && !line.trim().is_empty() {
+            let Some(last) = headers.last_mut() else {
+                break;
+            };
+            last.value.push(' ');
+            last.value.push_str(line.trim());
+        } else if let Some((name, value)) = line.split_once(':').filter(|(n, _)| is_field_name(n)) {
+            headers.push(EmailHeader {
+                name: name.to_string(),
+                value: value.trim().to_string(),
+                line: i as u32 + 1,
+            });
+        } else {
+            break;
+        }
+        i += 1;
+    }
+    for h in &mut headers {
+        h.value = decode_header_value(&h.value);
+    }
+    (headers, i)
+}
+
+fn header<'a>(headers: &'a [EmailHeader], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|h| h.name.eq_ignore_ascii_case(name))
+        .map(|h| h.value.as_str())
+}
+
+/// True for media types whose body is text worth showing: `text/*`, delivery reports, and
+/// structured types we have a kind for (JSON, XML, YAML, ...).
+fn is_text(content_type: &str) -> bool {
+    content_type.starts_with("text/")
+        || matches!(
+            content_type,
+            "message/delivery-status"
+                | "message/disposition-notification"
+                | "message/feedback-report"
+        )
+        || http::kind_for_content_type(content_type).is_some()
+}
+
+/// Parse the entity in `lines[start..end]`.
+fn parse_entity(lines: &[&str], start: usize, end: usize, depth: usize) -> MimePart {
+    let (headers, header_end) = header_block(lines, start, end);
+    let body_start = if lines.get(header_end).is_some_and(|l| l.trim().is_empty()) {
+        header_end + 1
+    } else {
+        header_end
+    };
+    let (content_type, type_params) =
+        parameters(header(&headers, "Content-Type").unwrap_or("text/plain"));
+    let content_type = if content_type.contains('/') {
+        content_type
+    } else {
+        "text/plain".to_string()
+    };
+    let (_, disposition_params) = parameters(header(&headers, "Content-Disposition").unwrap_or(""));
+    let transfer_encoding =
+        header(&headers, "Content-Transfer-Encoding").map(|e| e.trim().to_ascii_lowercase());
+    let mut part = MimePart {
+        charset: param(&type_params, "charset").map(str::to_string),
+        filename: param(&disposition_params, "filename")
+            .or_else(|| param(&type_params, "name"))
+            .map(str::to_string),
+        headers,
+        content_type,
+        transfer_encoding,
+        start_line: start as u32 + 1,
+        end_line: end as u32,
+        body_line: (body_start < end).then_some(body_start as u32 + 1),
+        text: None,
+        kind: None,
+        size: 0,
+        error: None,
+        parts: Vec::new(),
+    };
+    // Nesting beyond this is either hostile or broken; treat deeper entities as opaque.
+    let nested = depth < 16;
+    if part.content_type.starts_with("multipart/") && nested {
+        if let Some(boundary) = param(&type_params, "boundary") {
+            part.parts = multipart_children(lines, body_start, end, boundary, depth);
+            return part;
+        }
+    }
+    if part.content_type == "message/rfc822" && nested && body_start < end {
+        part.parts = vec![parse_entity(lines, body_start, end, depth + 1)];
+        return part;
+    }
+    let raw = lines[body_start.min(end)..end].join("\n");
+    let bytes = match part.transfer_encoding.as_deref() {
+        Some("base64") => {
+            let compact: String = raw.chars().filter(|c| !c.is_whitespace()).collect();
+            match BASE64.decode(compact) {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    part.error = Some(format!("invalid base64 body: {}", e));
+                    return part;
+                }
+            }
+        }
+        Some("quoted-printable") => quoted_printable(&raw),
+        _ => raw.into_bytes(),
+    };
+    part.size = bytes.len();
+    if is_text(&part.content_type) {
+        let text = decode_charset(&bytes, part.charset.as_deref());
+        let kind = http::kind_for_content_type(&part.content_type)
+            .unwrap_or_else(|| content_detection_heuristic(&text).0);
+        part.kind = Some(kind.to_string());
+        part.text = Some(text);
+    }
+    part
+}
+
+/// Split a multipart body at its boundary lines; the preamble and epilogue are not parts.
+fn multipart_children(
+    lines: &[&str],
+    start: usize,
+    end: usize,
+    boundary: &str,
+    depth: usize,
+) -> Vec<MimePart> {
+    let delimiter = format!("--{}", boundary);
+    let close = format!("--{}--", boundary);
+    let mut children = Vec::new();
+    let mut part_start: Option<usize> = None;
+    for i in start..end {
+        let line = lines[i].trim_end();
+        let is_close = line == close;
+        if line == delimiter || is_close {
+            if let Some(s) = part_start {
+                children.push(parse_entity(lines, s, i, depth + 1));
+            }
+            part_start = (!is_close).then_some(i + 1);
+            if is_close {
+                break;
+            }
+        }
+    }
+    if let Some(s) = part_start {
+        children.push(parse_entity(lines, s, end, depth + 1));
+    }
+    children
+}
+
+fn parse_message(content: &str) -> Option<MimePart> {
+    let lines: Vec<&str> = content.lines().collect();
+    let message = parse_entity(&lines, 0, lines.len(), 0);
+    (!message.headers.is_empty()).then_some(message)
+}
+
+/// True if the value holds an address (`user@host.tld`, bare or in angle brackets).
+fn has_address(value: &str) -> bool {
+    value
+        .split([' ', '<', '>', ',', '"'])
+        .filter_map(|word| word.split_once('@'))
+        .any(|(local, domain)| !local.is_empty() && domain.contains('.'))
+}
+
+/// True if the header is one only mail has: `MIME-Version`, `Received`, an address in
+/// `From`/`To`/..., an RFC 5322 `Date` or a `Message-ID`.
+fn is_mail_header(h: &EmailHeader) -> bool {
+    match h.name.to_ascii_lowercase().as_str() {
+        "mime-version" | "received" => true,
+        "from" | "to" | "cc" | "reply-to" | "delivered-to" | "message-id" => has_address(&h.value),
+        "date" => DateTime::parse_from_rfc2822(&h.value).is_ok(),
+        _ => false,
+    }
+}
+
+/// True if the text opens with a header block containing at least two common message fields
+/// (From, To, Subject, Date, Received, ...), one of which only mail has (see `is_mail_header`),
+/// and the block is closed by a blank line. A YAML mapping with `from:` and `to:` keys is not mail.
+pub fn looks_like_email(content: &str) -> bool {
+    let lines: Vec<&str> = content.lines().collect();
+    let (headers, end) = header_block(&lines, 0, lines.len());
+    let known = headers
+        .iter()
+        .filter(|h| MESSAGE_FIELDS.contains(&h.name.to_ascii_lowercase().as_str()))
+        .count();
+    known >= 2
+        && headers.iter().any(is_mail_header)
+        && lines.get(end).is_some_and(|l| l.trim().is_empty())
+}
+
+/// Segments for a message: every part body that is text as written (not base64 or quoted-printable)
+/// gets its own segment of the detected kind; headers, boundaries and encoded bodies are `email`.
+/// Covers every line. An encoded body's lines are not its text, so it has no segment of its own;
+/// `email_message` gives the decoded text and kind of every part.
+pub fn email_segments(content: &str) -> Vec<Segment> {
+    let line_count = content.lines().count().max(1) as u32;
+    let mut bodies: Vec<(u32, u32, String)> = Vec::new();
+    fn collect(part: &MimePart, out: &mut Vec<(u32, u32, String)>) {
+        for child in &part.parts {
+            collect(child, out);
+        }
+        let plain = !matches!(
+            part.transfer_encoding.as_deref(),
+            Some("base64" | "quoted-printable")
+        );
+        if let (Some(kind), Some(line), true) = (&part.kind, part.body_line, plain) {
+            if line <= part.end_line {
+                out.push((line, part.end_line, kind.clone()));
+            }
+        }
+    }
+    if let Some(message) = parse_message(content) {
+        collect(&message, &mut bodies);
+    }
+    bodies.sort_by_key(|b| b.0);
+    let mut segments: Vec<Segment> = Vec::new();
+    let mut next = 1;
+    for (start, end, kind) in bodies {
+        if start > next {
+            segments.push(Segment {
+                start_line: next,
+                end_line: start - 1,
+                kind: "email".to_string(),
+                label: None,
+                dialect: None,
+            });
+        }
+        segments.push(Segment {
+            start_line: start,
+            end_line: end,
+            kind,
+            label: None,
+            dialect: None,
+        });
+        next = end + 1;
+    }
+    if next <= line_count {
+        segments.push(Segment {
+            start_line: next,
+            end_line: line_count,
+            kind: "email".to_string(),
+            label: None,
+            dialect: None,
+        });
+    }
+    segments
+}
+
+/// Parse a raw email: decoded headers and the MIME tree, with each text part decoded and its
+/// content kind detected. This is where base64 and quoted-printable parts are read: they have no
+/// segment in the buffer (see `email_segments`), so open their `text` on its own.
+#[tauri::command]
+pub fn email_message(content: String) -> Result<MimePart, String> {
+    parse_message(&content).ok_or_else(|| "no message header found".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNCE: &str = "Return-Path: <>\nFrom: Mail Delivery System <MAILER-DAEMON@mx.example.com>\nTo: support@example.com\nSubject: =?UTF-8?Q?Undelivered_Mail_=E2=80=93?= =?utf-8?B?UmV0dXJu?=\nMIME-Version: 1.0\nContent-Type: multipart/report; report-type=delivery-status;\n\tboundary=\"B1\"\n\nThis is a MIME-encapsulated message.\n\n--B1\nContent-Type: text/plain; charset=iso-8859-1\nContent-Transfer-Encoding: quoted-printable\n\nDelivery to caf=E9@example.org failed =\npermanently.\n--B1\nContent-Type: message/delivery-status\n\nStatus: 5.1.1\nAction: failed\n--B1\nContent-Type: application/json\nContent-Transfer-Encoding: base64\n\neyJldmVudCI6ImJvdW5jZSJ9\n--B1--\n";
+
+    #[test]
+    fn decode_header_value_joins_adjacent_encoded_words() {
+        assert_eq!(
+            decode_header_value("=?UTF-8?Q?Undelivered_Mail_=E2=80=93?= =?utf-8?B?UmV0dXJu?="),
+            "Undelivered Mail –Return"
+        );
+        assert_eq!(
+            decode_header_value("Re: =?ISO-8859-1?Q?Caf=E9?= menu"),
+            "Re: Café menu"
+        );
+        assert_eq!(decode_header_value("plain =? text"), "plain =? text");
+    }
+
+    #[test]
+    fn email_message_builds_the_multipart_tree() {
+        let message = email_message(BOUNCE.to_string()).unwrap();
+        assert_eq!(message.content_type, "multipart/report");
+        assert_eq!(
+            header(&message.headers, "subject"),
+            Some("Undelivered Mail –Return")
+        );
+        assert_eq!(message.parts.len(), 3);
+        let notice = &message.parts[0];
+        assert_eq!(
+            notice.text.as_deref(),
+            Some("Delivery to café@example.org failed permanently.")
+        );
+        assert_eq!(notice.start_line, 12);
+        let status = &message.parts[1];
+        assert_eq!(status.content_type, "message/delivery-status");
+        assert_eq!(status.body_line, Some(20));
+        let payload = &message.parts[2];
+        assert_eq!(payload.text.as_deref(), Some("{\"event\":\"bounce\"}"));
+        assert_eq!(payload.kind.as_deref(), Some("json"));
+    }
+
+    #[test]
+    fn email_segments_cover_the_message() {
+        assert!(looks_like_email(BOUNCE));
+        assert!(!looks_like_email("Subject: only one field\n\nbody"));
+        assert!(!looks_like_email(
+            "from: alice\nto: bob\nsubject: hi\nbody:\n  text: hello"
+        ));
+        assert!(!looks_like_email("from: alice\nto: bob\n\nsubject: hi"));
+        assert!(looks_like_email(
+            "Date: Tue, 7 May 2024 10:00:00 +0000\nSubject: hi\n\nbody"
+        ));
+        let segs: Vec<(u32, u32, String)> = email_segments(BOUNCE)
+            .into_iter()
+            .map(|s| (s.start_line, s.end_line, s.kind))
+            .collect();
+        assert_eq!(
+            segs,
+            vec![
+                (1, 19, "email".to_string()),
+                (20, 21, "yaml".to_string()),
+                (22, 27, "email".to_string()),
+            ]
+        );
+    }
+}
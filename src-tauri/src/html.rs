@@ -0,0 +1,574 @@
+//! HTML: lenient tokenizer, block/inline-aware formatter and text extraction.
+//!
+//! Unlike `format_xml`, nothing here requires well-formed markup: void elements, unquoted
+//! attributes, omitted end tags and stray `<` are all accepted and passed through.
+
+/// Elements laid out on their own lines and indented by the formatter. Everything else is inline.
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "body",
+    "caption",
+    "center",
+    "colgroup",
+    "dd",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "head",
+    "header",
+    "hr",
+    "html",
+    "li",
+    "link",
+    "main",
+    "menu",
+    "meta",
+    "nav",
+    "noscript",
+    "ol",
+    "optgroup",
+    "option",
+    "p",
+    "pre",
+    "script",
+    "section",
+    "select",
+    "style",
+    "summary",
+    "table",
+    "tbody",
+    "td",
+    "template",
+    "tfoot",
+    "th",
+    "thead",
+    "title",
+    "tr",
+    "ul",
+];
+
+/// Elements that never have content or an end tag.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Elements whose content is kept byte-for-byte (and not parsed for tags).
+const VERBATIM_ELEMENTS: &[&str] = &["pre", "script", "style", "textarea"];
+
+/// Block elements after which extracted text gets a blank line rather than a single line break.
+const PARAGRAPH_ELEMENTS: &[&str] = &[
+    "blockquote",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "ol",
+    "p",
+    "pre",
+    "table",
+    "ul",
+];
+
+/// Inline elements common enough to identify a fragment as HTML.
+const COMMON_INLINE: &[&str] = &[
+    "a", "b", "button", "code", "em", "i", "img", "label", "small", "span", "strong",
+];
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Text(&'a str),
+    /// `<!-- ... -->`
+    Comment(&'a str),
+    /// `<!DOCTYPE ...>` or `<? ... ?>`
+    Declaration(&'a str),
+    Start {
+        name: String,
+        raw: &'a str,
+    },
+    End {
+        name: String,
+        raw: &'a str,
+    },
+    /// A verbatim element: its start tag, untouched content and end tag (empty if missing).
+    Verbatim {
+        name: String,
+        open: &'a str,
+        content: &'a str,
+        close: &'a str,
+    },
+}
+
+fn is_block(name: &str) -> bool {
+    BLOCK_ELEMENTS.contains(&name)
+}
+
+fn is_void(name: &str) -> bool {
+    VOID_ELEMENTS.contains(&name)
+}
+
+/// Byte index just past the `>` closing a tag that starts at `start`, skipping quoted attribute values.
+fn tag_end(s: &str, start: usize) -> usize {
+    let mut quote = None;
+    for (i, c) in s[start..].char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '>') => return start + i + 1,
+            _ => {}
+        }
+    }
+    s.len()
+}
+
+fn tag_name(s: &str) -> String {
+    s.chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == ':')
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+/// Find `</name` (case-insensitive) at or after `from`.
+fn find_close(s: &str, from: usize, name: &str) -> Option<usize> {
+    let needle = format!("</{}", name);
+    s[from..]
+        .to_ascii_lowercase()
+        .find(&needle)
+        .map(|i| from + i)
+}
+
+fn tokenize(s: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let b = s.as_bytes();
+    let mut text_start = 0;
+    let mut i = 0;
+    while i < b.len() {
+        if b[i] != b'<' {
+            i += 1;
+            continue;
+        }
+        let rest = &s[i..];
+        let next = rest[1..].chars().next();
+        let (token, end) = if rest.starts_with("<!--") {
+            let end = rest.find("-->").map_or(s.len(), |e| i + e + 3);
+            (Token::Comment(&s[i..end]), end)
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            let end = tag_end(s, i);
+            (Token::Declaration(&s[i..end]), end)
+        } else if rest.starts_with("</") && rest[2..].starts_with(|c: char| c.is_ascii_alphabetic())
+        {
+            let end = tag_end(s, i);
+            let name = tag_name(&rest[2..]);
+            (
+                Token::End {
+                    name,
+                    raw: &s[i..end],
+                },
+                end,
+            )
+        } else if next.is_some_and(|c| c.is_ascii_alphabetic()) {
+            let open_end = tag_end(s, i);
+            let name = tag_name(&rest[1..]);
+            let self_closing = s[i..open_end].ends_with("/>");
+            if VERBATIM_ELEMENTS.contains(&name.as_str()) && !self_closing {
+                let close_start = find_close(s, open_end, &name).unwrap_or(s.len());
+                let close_end = if close_start < s.len() {
+                    tag_end(s, close_start)
+                } else {
+                    s.len()
+                };
+                let token = Token::Verbatim {
+                    name,
+                    open: &s[i..open_end],
+                    content: &s[open_end..close_start],
+                    close: &s[close_start..close_end],
+                };
+                (token, close_end)
+            } else {
+                (
+                    Token::Start {
+                        name,
+                        raw: &s[i..open_end],
+                    },
+                    open_end,
+                )
+            }
+        } else {
+            // A bare `<` in text.
+            i += 1;
+            continue;
+        };
+        if text_start < i {
+            tokens.push(Token::Text(&s[text_start..i]));
+        }
+        tokens.push(token);
+        i = end;
+        text_start = end;
+    }
+    if text_start < s.len() {
+        tokens.push(Token::Text(&s[text_start..]));
+    }
+    tokens
+}
+
+/// Collapse whitespace runs outside quoted attribute values to one space; drop the space before `>`.
+fn normalize_tag(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut quote = None;
+    for c in raw.chars() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, c) if c.is_whitespace() => {
+                if !out.ends_with(' ') {
+                    out.push(' ');
+                }
+                continue;
+            }
+            _ => {}
+        }
+        out.push(c);
+    }
+    match out.strip_suffix(" >") {
+        Some(head) => format!("{}>", head),
+        None => out,
+    }
+}
+
+/// Collapse runs of ASCII whitespace (not `&nbsp;`) to one space, as HTML rendering does.
+fn collapse_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_ascii_whitespace() {
+            if !out.ends_with(' ') {
+                out.push(' ');
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// True if opening `next` implicitly ends the still-open `open` element (HTML's optional end tags).
+fn implicitly_closes(open: &str, next: &str) -> bool {
+    match open {
+        "p" => is_block(next),
+        "li" => next == "li",
+        "dt" | "dd" => matches!(next, "dt" | "dd"),
+        "td" | "th" => matches!(next, "td" | "th" | "tr" | "tbody" | "tfoot"),
+        "tr" => matches!(next, "tr" | "tbody" | "tfoot"),
+        "thead" | "tbody" => matches!(next, "tbody" | "tfoot"),
+        "option" => matches!(next, "option" | "optgroup"),
+        _ => false,
+    }
+}
+
+/// Collects output lines: block elements on their own indented lines, inline runs joined.
+struct Printer<'a> {
+    lines: Vec<String>,
+    inline: String,
+    open: Vec<String>,
+    /// One level of indentation.
+    unit: &'a str,
+}
+
+impl Printer<'_> {
+    fn indent(&self) -> String {
+        self.unit.repeat(self.open.len())
+    }
+
+    fn flush(&mut self) {
+        let run = self.inline.trim();
+        if !run.is_empty() {
+            self.lines.push(format!("{}{}", self.indent(), run));
+        }
+        self.inline.clear();
+    }
+
+    fn line(&mut self, text: &str) {
+        self.flush();
+        self.lines.push(format!("{}{}", self.indent(), text));
+    }
+
+    fn start_block(&mut self, name: &str, raw: &str) {
+        while self.open.last().is_some_and(|o| implicitly_closes(o, name)) {
+            self.flush();
+            self.open.pop();
+        }
+        self.line(&normalize_tag(raw));
+        if !is_void(name) && !raw.ends_with("/>") {
+            self.open.push(name.to_string());
+        }
+    }
+
+    fn end_block(&mut self, name: &str, raw: &str) {
+        self.flush();
+        if let Some(pos) = self.open.iter().rposition(|o| o == name) {
+            self.open.truncate(pos);
+        }
+        self.line(&normalize_tag(raw));
+    }
+
+    fn verbatim(&mut self, name: &str, open: &str, content: &str, close: &str) {
+        match name {
+            "script" | "style" if !content.trim().is_empty() => {
+                self.line(&normalize_tag(open));
+                self.lines
+                    .push(content.trim_matches(['\n', '\r']).trim_end().to_string());
+                self.line(close);
+            }
+            "textarea" => {
+                self.inline
+                    .push_str(&format!("{}{}{}", normalize_tag(open), content, close))
+            }
+            _ => self.line(&format!("{}{}{}", normalize_tag(open), content, close)),
+        }
+    }
+}
+
+/// Re-indent HTML: block elements on their own lines, inline content kept on one line with its
+/// whitespace collapsed, and `<pre>`, `<textarea>`, `<script>`, `<style>` content left untouched.
+/// Each level is indented by `indent`.
+pub fn format_html(content: &str, indent: &str) -> String {
+    let mut p = Printer {
+        lines: Vec::new(),
+        inline: String::new(),
+        open: Vec::new(),
+        unit: indent,
+    };
+    for token in tokenize(content) {
+        match token {
+            Token::Text(text) => p.inline.push_str(&collapse_whitespace(text)),
+            Token::Comment(raw) | Token::Declaration(raw) => p.line(raw.trim()),
+            Token::Start { name, raw } if is_block(&name) => p.start_block(&name, raw),
+            Token::End { name, raw } if is_block(&name) => p.end_block(&name, raw),
+            Token::Start { name, raw } => {
+                p.inline.push_str(&normalize_tag(raw));
+                if name == "br" {
+                    p.flush();
+                }
+            }
+            Token::End { raw, .. } => p.inline.push_str(&normalize_tag(raw)),
+            Token::Verbatim {
+                name,
+                open,
+                content,
+                close,
+            } => p.verbatim(&name, open, content, close),
+        }
+    }
+    p.flush();
+    p.lines.join("\n")
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    if let Some(num) = entity.strip_prefix('#') {
+        let code = match num.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => num.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match entity {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "hellip" => '…',
+        "mdash" => '—',
+        "ndash" => '–',
+        "laquo" => '«',
+        "raquo" => '»',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "euro" => '€',
+        _ => return None,
+    })
+}
+
+/// Replace character references (`&amp;`, `&#39;`, `&#x2F;`, ...); unknown ones are kept as written.
+pub fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|&semi| semi <= 32)
+            .and_then(|semi| decode_entity(&rest[1..semi + 1]).map(|c| (c, semi + 2)));
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// End the current line of extracted text; with `blank`, also leave an empty line after it.
+fn break_line(text: &mut String, blank: bool) {
+    text.truncate(text.trim_end_matches([' ', '\t']).len());
+    if text.is_empty() {
+        return;
+    }
+    let have = text.len() - text.trim_end_matches('\n').len();
+    let wanted = if blank { 2 } else { 1 };
+    for _ in have..wanted {
+        text.push('\n');
+    }
+}
+
+/// Visible text of an HTML document: tags, scripts and styles removed, entities decoded,
+/// block elements on separate lines and `<pre>` content kept as written.
+#[tauri::command]
+pub fn html_text_content(content: String) -> String {
+    let mut text = String::new();
+    for token in tokenize(&content) {
+        match token {
+            Token::Text(t) => {
+                let collapsed = collapse_whitespace(&decode_entities(t));
+                let at_line_start = text.is_empty() || text.ends_with('\n');
+                text.push_str(if at_line_start {
+                    collapsed.trim_start()
+                } else {
+                    &collapsed
+                });
+            }
+            Token::Start { name, .. } | Token::End { name, .. } if name == "br" => {
+                text.truncate(text.trim_end_matches(' ').len());
+                text.push('\n');
+            }
+            Token::Start { name, .. } if is_block(&name) => break_line(&mut text, false),
+            Token::End { name, .. } if is_block(&name) => {
+                break_line(&mut text, PARAGRAPH_ELEMENTS.contains(&name.as_str()))
+            }
+            Token::Verbatim { name, content, .. } if name == "pre" || name == "textarea" => {
+                break_line(&mut text, false);
+                for inner in tokenize(content) {
+                    if let Token::Text(t) = inner {
+                        text.push_str(&decode_entities(t));
+                    }
+                }
+                break_line(&mut text, true);
+            }
+            _ => {}
+        }
+    }
+    text.trim().to_string()
+}
+
+/// True if the content starts like an HTML document (`<!DOCTYPE html>`, `<html>`) or its first
+/// tag is a common HTML element.
+pub fn looks_like_html(content: &str) -> bool {
+    let trimmed = content.trim_start();
+    if !trimmed.starts_with('<') {
+        return false;
+    }
+    let head: String = trimmed
+        .chars()
+        .take(64)
+        .collect::<String>()
+        .to_ascii_lowercase();
+    if head.starts_with("<!doctype html") || head.starts_with("<html") {
+        return true;
+    }
+    let name = tag_name(head.trim_start_matches(['<', '/']));
+    is_block(&name) || is_void(&name) || COMMON_INLINE.contains(&name.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_accepts_html5_syntax() {
+        let tokens = tokenize("<p class=lead>a<br>b < c</p>");
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Start {
+                    name: "p".into(),
+                    raw: "<p class=lead>"
+                },
+                Token::Text("a"),
+                Token::Start {
+                    name: "br".into(),
+                    raw: "<br>"
+                },
+                Token::Text("b < c"),
+                Token::End {
+                    name: "p".into(),
+                    raw: "</p>"
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn format_html_indents_blocks_and_keeps_inline_runs() {
+        let raw = "<!DOCTYPE html><html><body><div class=\"a\"   id=x><p>Hello <b>big</b>\n   world</p><ul><li>one<li>two</ul></div></body></html>";
+        let out = format_html(raw, "  ");
+        assert_eq!(
+            out,
+            "<!DOCTYPE html>\n<html>\n  <body>\n    <div class=\"a\" id=x>\n      <p>\n        Hello <b>big</b> world\n      </p>\n      <ul>\n        <li>\n          one\n        <li>\n          two\n      </ul>\n    </div>\n  </body>\n</html>"
+        );
+    }
+
+    #[test]
+    fn format_html_leaves_pre_and_script_untouched() {
+        let raw = "<div><pre>  a\n    b</pre><script>\nif (a < b) { x(); }\n</script></div>";
+        let out = format_html(raw, "  ");
+        assert_eq!(
+            out,
+            "<div>\n  <pre>  a\n    b</pre>\n  <script>\nif (a < b) { x(); }\n  </script>\n</div>"
+        );
+    }
+
+    #[test]
+    fn html_text_content_extracts_visible_text() {
+        let raw = "<html><head><title>T</title><style>p{}</style></head><body><h1>Hi &amp; bye</h1><p>one<br>two&nbsp;three</p><script>x()</script><pre>a\n  b</pre></body></html>";
+        let out = html_text_content(raw.to_string());
+        assert_eq!(out, "T\nHi & bye\n\none\ntwo\u{a0}three\n\na\n  b");
+    }
+
+    #[test]
+    fn looks_like_html_checks_leading_tag() {
+        assert!(looks_like_html("<!doctype html><p>x"));
+        assert!(looks_like_html("<div>\n<span>x</span></div>"));
+        assert!(!looks_like_html("<config><item/></config>"));
+        assert!(!looks_like_html("plain <b>text</b>"));
+    }
+}
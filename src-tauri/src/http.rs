@@ -0,0 +1,516 @@
+//! HTTP messages (raw dumps of requests and responses): start line, headers and body.
+
+use std::io::Read;
+
+use flate2::read::{GzDecoder, ZlibDecoder};
+use serde::Serialize;
+
+use crate::{content_detection_heuristic, format_segment, FormatOptions};
+
+const METHODS: &[&str] = &[
+    "GET", "HEAD", "POST", "PUT", "DELETE", "CONNECT", "OPTIONS", "TRACE", "PATCH",
+];
+
+/// Request line or status line.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StartLine {
+    Request {
+        method: String,
+        target: String,
+        version: String,
+    },
+    Response {
+        version: String,
+        status: u16,
+        reason: String,
+    },
+}
+
+/// A header field (1-based line; folded continuation lines are joined into the value).
+#[derive(Debug, Serialize)]
+pub struct HttpHeader {
+    pub name: String,
+    pub value: String,
+    pub line: u32,
+}
+
+/// One HTTP message found in the buffer (1-based inclusive lines).
+#[derive(Debug, Serialize)]
+pub struct HttpMessage {
+    pub start: StartLine,
+    pub start_line: u32,
+    pub end_line: u32,
+    pub headers: Vec<HttpHeader>,
+    /// Body with its transfer and content encodings removed.
+    pub body: String,
+    /// Kind of the body, from its Content-Type or detected from the body itself.
+    pub body_kind: String,
+    /// 1-based line where the body starts, if there is a body.
+    pub body_line: Option<u32>,
+    /// Encodings that were removed, in the order they were undone (`chunked`, `gzip`, `deflate`).
+    pub decoded: Vec<String>,
+    /// Set when the body could not be decoded; `body` is then the body as written.
+    pub error: Option<String>,
+}
+
+fn is_version(v: &str) -> bool {
+    v.strip_prefix("HTTP/")
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit() || c == '.'))
+}
+
+fn start_line(line: &str) -> Option<StartLine> {
+    let mut parts = line.trim().splitn(3, ' ');
+    let first = parts.next()?;
+    if is_version(first) {
+        let status = parts.next()?;
+        if status.len() != 3 || !status.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        return Some(StartLine::Response {
+            version: first.to_string(),
+            status: status.parse().ok()?,
+            reason: parts.next().unwrap_or("").to_string(),
+        });
+    }
+    if !METHODS.contains(&first) {
+        return None;
+    }
+    let target = parts.next()?;
+    let version = parts.next()?;
+    is_version(version).then(|| StartLine::Request {
+        method: first.to_string(),
+        target: target.to_string(),
+        version: version.to_string(),
+    })
+}
+
+/// `Name: value`, where the name is an HTTP token.
+fn header_field(line: &str) -> Option<(&str, &str)> {
+    let (name, value) = line.split_once(':')?;
+    let token = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_!#$%&'*+.^`|~".contains(c));
+    token.then(|| (name, value.trim()))
+}
+
+fn header<'a>(headers: &'a [HttpHeader], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|h| h.name.eq_ignore_ascii_case(name))
+        .map(|h| h.value.as_str())
+}
+
+fn is_chunked(headers: &[HttpHeader]) -> bool {
+    header(headers, "Transfer-Encoding").is_some_and(|v| v.to_ascii_lowercase().contains("chunked"))
+}
+
+/// A message parsed from the start of a slice of lines; indices are relative to the slice.
+struct Parsed {
+    start: StartLine,
+    headers: Vec<HttpHeader>,
+    /// Number of lines of the start line and header fields.
+    head_len: usize,
+    /// Index of the first body line and number of body lines.
+    body: Option<(usize, usize)>,
+    len: usize,
+}
+
+/// Number of body lines, from the framing the headers describe: chunked up to the last chunk,
+/// `Content-Length` bytes, nothing for a request without either, and otherwise (a response read
+/// until the connection closed) everything up to the next message.
+fn body_len(lines: &[&str], request: bool, headers: &[HttpHeader]) -> usize {
+    let len = if is_chunked(headers) {
+        match lines
+            .iter()
+            .position(|l| l.trim().split(';').next() == Some("0"))
+        {
+            // The last chunk may be followed by trailer fields.
+            Some(last) => {
+                last + 1
+                    + lines[last + 1..]
+                        .iter()
+                        .take_while(|l| header_field(l).is_some())
+                        .count()
+            }
+            None => lines.len(),
+        }
+    } else if let Some(length) =
+        header(headers, "Content-Length").and_then(|v| v.parse::<usize>().ok())
+    {
+        let mut bytes = 0;
+        lines
+            .iter()
+            .take_while(|l| {
+                let more = bytes < length;
+                bytes += l.len() + 1;
+                more
+            })
+            .count()
+    } else if request {
+        0
+    } else {
+        lines
+            .iter()
+            .position(|l| start_line(l).is_some())
+            .unwrap_or(lines.len())
+    };
+    len - lines[..len]
+        .iter()
+        .rev()
+        .take_while(|l| l.trim().is_empty())
+        .count()
+}
+
+fn parse(lines: &[&str], first: usize) -> Option<Parsed> {
+    let start = start_line(lines.first()?)?;
+    let mut headers: Vec<HttpHeader> = Vec::new();
+    let mut i = 1;
+    while let Some(line) = lines.get(i) {
+        if line.starts_with([' ', '\t']) && !line.trim().is_empty() {
+            // Obsolete line folding: continues the previous field's value.
+            let Some(last) = headers.last_mut() else {
+                break;
+            };
+            if !last.value.is_empty() {
+                last.value.push(' ');
+            }
+            last.value.push_str(line.trim());
+        } else if let Some((name, value)) = header_field(line) {
+            headers.push(HttpHeader {
+                name: name.to_string(),
+                value: value.to_string(),
+                line: (first + i + 1) as u32,
+            });
+        } else {
+            break;
+        }
+        i += 1;
+    }
+    let head_len = i;
+    if !lines.get(i).is_some_and(|l| l.trim().is_empty()) {
+        return Some(Parsed {
+            start,
+            headers,
+            head_len,
+            body: None,
+            len: head_len,
+        });
+    }
+    let request = matches!(start, StartLine::Request { .. });
+    let body_start = i + 1;
+    let len = body_len(&lines[body_start..], request, &headers);
+    Some(Parsed {
+        start,
+        headers,
+        head_len,
+        body: (len > 0).then_some((body_start, len)),
+        len: if len > 0 { body_start + len } else { head_len },
+    })
+}
+
+/// Bytes of lines `start..start + len` of a buffer, line endings inside kept as written.
+fn line_bytes(bytes: &[u8], start: usize, len: usize) -> &[u8] {
+    let mut starts = std::iter::once(0).chain(
+        bytes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| **b == b'\n')
+            .map(|(i, _)| i + 1),
+    );
+    let from = starts.nth(start).unwrap_or(bytes.len());
+    let to = match len {
+        0 => from,
+        _ => starts.nth(len - 1).map_or(bytes.len(), |s| s - 1),
+    };
+    &bytes[from..to.max(from)]
+}
+
+fn dechunk(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    let mut rest = bytes;
+    loop {
+        let eol = rest.iter().position(|b| *b == b'\n').unwrap_or(rest.len());
+        let size_line = String::from_utf8_lossy(&rest[..eol]);
+        let size_hex = size_line.split(';').next().unwrap_or("").trim();
+        let size = usize::from_str_radix(size_hex, 16)
+            .map_err(|_| format!("invalid chunk size: {}", size_line.trim()))?;
+        if size == 0 {
+            return Ok(out);
+        }
+        rest = rest.get(eol + 1..).unwrap_or(&[]);
+        let data = rest.get(..size).ok_or("chunk is longer than the body")?;
+        out.extend_from_slice(data);
+        rest = &rest[size..];
+        rest = rest.strip_prefix(b"\r").unwrap_or(rest);
+        rest = rest.strip_prefix(b"\n").unwrap_or(rest);
+    }
+}
+
+fn decompress(bytes: &[u8], encoding: &str) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    let result = match encoding {
+        "gzip" | "x-gzip" => GzDecoder::new(bytes).read_to_end(&mut out),
+        "deflate" => ZlibDecoder::new(bytes).read_to_end(&mut out),
+        other => return Err(format!("unsupported content encoding: {}", other)),
+    };
+    result.map_err(|e| format!("{} body could not be decoded: {}", encoding, e))?;
+    Ok(out)
+}
+
+/// Undo transfer and content encodings; returns the body and the encodings removed.
+fn decode_body(raw: &[u8], headers: &[HttpHeader]) -> Result<(String, Vec<String>), String> {
+    let mut decoded = Vec::new();
+    let encodings: Vec<String> = header(headers, "Content-Encoding")
+        .unwrap_or("")
+        .split(',')
+        .map(|e| e.trim().to_ascii_lowercase())
+        .filter(|e| !e.is_empty() && e != "identity")
+        .collect();
+    if !is_chunked(headers) && encodings.is_empty() {
+        return Ok((String::from_utf8_lossy(raw).into_owned(), decoded));
+    }
+    let mut bytes = raw.to_vec();
+    if is_chunked(headers) {
+        bytes = dechunk(&bytes)?;
+        decoded.push("chunked".to_string());
+    }
+    // Content codings are listed in the order they were applied.
+    for encoding in encodings.iter().rev() {
+        bytes = decompress(&bytes, encoding)?;
+        decoded.push(encoding.clone());
+    }
+    let body = String::from_utf8(bytes).map_err(|_| "body is not UTF-8 text".to_string())?;
+    Ok((body, decoded))
+}
+
+/// Kind for a Content-Type's media type, when it names one we can format.
+pub fn kind_for_content_type(content_type: &str) -> Option<&'static str> {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    let kind = match mime.as_str() {
+        "application/x-ndjson" | "application/jsonl" | "application/x-jsonlines" => "ndjson",
+        m if m.ends_with("/json") || m.ends_with("+json") => "json",
+        "text/html" => "html",
+        m if m.ends_with("/xml") || m.ends_with("+xml") => "xml",
+        "text/csv" | "text/tab-separated-values" => "csv",
+        m if m.ends_with("yaml") => "yaml",
+        "application/toml" => "toml",
+        "application/sql" => "sql",
+        "text/markdown" => "markdown",
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// Build a message from its parsed lines. With the buffer's `bytes`, the body is taken from them
+/// as written, so binary (compressed) bodies survive; otherwise it is the body lines rejoined.
+fn message(lines: &[&str], first: usize, parsed: Parsed, bytes: Option<&[u8]>) -> HttpMessage {
+    let raw: Vec<u8> = match (parsed.body, bytes) {
+        (Some((start, len)), Some(bytes)) => line_bytes(bytes, first + start, len).to_vec(),
+        (Some((start, len)), None) => lines[start..start + len].join("\n").into_bytes(),
+        (None, _) => Vec::new(),
+    };
+    let (body, decoded, error) = match decode_body(&raw, &parsed.headers) {
+        Ok((body, decoded)) => (body, decoded, None),
+        Err(e) => (
+            String::from_utf8_lossy(&raw).into_owned(),
+            Vec::new(),
+            Some(e),
+        ),
+    };
+    let body_kind = header(&parsed.headers, "Content-Type")
+        .and_then(kind_for_content_type)
+        .unwrap_or_else(|| content_detection_heuristic(&body).0)
+        .to_string();
+    HttpMessage {
+        start: parsed.start,
+        start_line: first as u32 + 1,
+        end_line: (first + parsed.len) as u32,
+        headers: parsed.headers,
+        body,
+        body_kind,
+        body_line: parsed.body.map(|(start, _)| (first + start + 1) as u32),
+        decoded,
+        error,
+    }
+}
+
+/// Number of lines of the HTTP message starting at `lines[0]`, if one starts there.
+pub fn message_len(lines: &[&str]) -> Option<usize> {
+    parse(lines, 0).map(|p| p.len)
+}
+
+/// True if the text starts with an HTTP request line or status line.
+pub fn looks_like_http(content: &str) -> bool {
+    content
+        .lines()
+        .next()
+        .is_some_and(|l| start_line(l).is_some())
+}
+
+/// Headers describing the body as sent; they no longer hold once it has been decoded.
+const FRAMING_HEADERS: &[&str] = &["Transfer-Encoding", "Content-Encoding", "Content-Length"];
+
+/// Format HTTP messages: the start line and headers as written, then the decoded body formatted
+/// as the kind its Content-Type names (or the kind detected from it). When the body was de-chunked
+/// or decompressed, its framing headers are left out. Other lines are kept.
+pub fn format_http(content: &str, options: &FormatOptions) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut out: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let Some(parsed) = parse(&lines[i..], 0) else {
+            out.push(lines[i].to_string());
+            i += 1;
+            continue;
+        };
+        let (head_len, len) = (parsed.head_len, parsed.len);
+        let has_body = parsed.body.is_some();
+        let msg = message(&lines[i..], 0, parsed, None);
+        let mut dropped = false;
+        for line in &lines[i..i + head_len] {
+            // Folded continuation lines go with their field.
+            if !line.starts_with([' ', '\t']) {
+                dropped = !msg.decoded.is_empty()
+                    && header_field(line).is_some_and(|(name, _)| {
+                        FRAMING_HEADERS.iter().any(|h| h.eq_ignore_ascii_case(name))
+                    });
+            }
+            if !dropped {
+                out.push(line.trim_end().to_string());
+            }
+        }
+        if has_body {
+            out.push(String::new());
+            if msg.error.is_some() {
+                out.push(msg.body);
+            } else {
+                out.push(format_segment(&msg.body_kind, msg.body, None, options));
+            }
+        }
+        i += len;
+    }
+    out.join("\n")
+}
+
+fn messages(content: &str, bytes: Option<&[u8]>) -> Vec<HttpMessage> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut messages = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        match parse(&lines[i..], i) {
+            Some(parsed) => {
+                let len = parsed.len;
+                messages.push(message(&lines[i..], i, parsed, bytes));
+                i += len;
+            }
+            None => i += 1,
+        }
+    }
+    messages
+}
+
+/// Parse the HTTP messages in a buffer (a request and its response, a proxy dump, ...): start line,
+/// headers, and the body decoded and classified by its Content-Type. Compressed bodies do not
+/// survive as text; use `http_file_messages` to decode those.
+#[tauri::command]
+pub fn http_messages(content: String) -> Vec<HttpMessage> {
+    messages(&content, None)
+}
+
+/// Parse the HTTP messages of a capture file. Bodies are read from the file's bytes, so gzip and
+/// deflate bodies can be decompressed.
+#[tauri::command]
+pub fn http_file_messages(path: String) -> Result<Vec<HttpMessage>, String> {
+    let bytes = std::fs::read(&path).map_err(|e| e.to_string())?;
+    // Lossy decoding keeps every newline, so line numbers still index into `bytes`.
+    let content = String::from_utf8_lossy(&bytes);
+    Ok(messages(&content, Some(&bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const EXCHANGE: &str = "POST /api/orders HTTP/1.1\nHost: example.com\nContent-Type: application/json\nContent-Length: 15\n\n{\"item\":\"book\"}\n\nHTTP/1.1 201 Created\nContent-Type: application/json; charset=utf-8\nX-Trace:\n  abc\n\n{\"id\":7}\n";
+
+    #[test]
+    fn http_messages_parse_request_and_response() {
+        let messages = http_messages(EXCHANGE.to_string());
+        assert_eq!(messages.len(), 2);
+        let req = &messages[0];
+        assert!(
+            matches!(&req.start, StartLine::Request { method, target, .. } if method == "POST" && target == "/api/orders")
+        );
+        assert_eq!(
+            (req.start_line, req.end_line, req.body_line),
+            (1, 6, Some(6))
+        );
+        assert_eq!(req.headers[1].name, "Content-Type");
+        assert_eq!(req.headers[1].line, 3);
+        assert_eq!(req.body_kind, "json");
+        let res = &messages[1];
+        assert!(matches!(res.start, StartLine::Response { status: 201, .. }));
+        assert_eq!((res.start_line, res.end_line), (8, 13));
+        assert_eq!(res.headers[1].value, "abc");
+        assert_eq!(res.body, "{\"id\":7}");
+    }
+
+    #[test]
+    fn chunked_and_gzip_bodies_are_decoded() {
+        let chunked = "HTTP/1.1 200 OK\nTransfer-Encoding: chunked\nContent-Type: application/json\n\n6\n{\"a\":1\n1\n}\n0\n\n";
+        let msg = &http_messages(chunked.to_string())[0];
+        assert_eq!(msg.body, "{\"a\":1}");
+        assert_eq!(msg.decoded, vec!["chunked"]);
+        assert_eq!(msg.end_line, 9);
+
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(b"{\"zipped\":true}").unwrap();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\n\r\n")
+            .unwrap();
+        file.write_all(&gz.finish().unwrap()).unwrap();
+        let path = file.path().to_string_lossy().to_string();
+        let msg = &http_file_messages(path).unwrap()[0];
+        assert_eq!(msg.error, None);
+        assert_eq!(msg.decoded, vec!["gzip"]);
+        assert_eq!(msg.body, "{\"zipped\":true}");
+        assert_eq!(msg.body_kind, "json");
+    }
+
+    #[test]
+    fn format_http_formats_body_by_content_type() {
+        let out = format_http(EXCHANGE, &FormatOptions::default());
+        assert_eq!(
+            out,
+            "POST /api/orders HTTP/1.1\nHost: example.com\nContent-Type: application/json\nContent-Length: 15\n\n{\n  \"item\": \"book\"\n}\n\nHTTP/1.1 201 Created\nContent-Type: application/json; charset=utf-8\nX-Trace:\n  abc\n\n{\n  \"id\": 7\n}"
+        );
+    }
+
+    #[test]
+    fn format_http_drops_framing_headers_of_decoded_bodies() {
+        let chunked = "HTTP/1.1 200 OK\nTransfer-Encoding:\n  chunked\nContent-Type: application/json\n\n6\n{\"a\":1\n1\n}\n0\n";
+        assert_eq!(
+            format_http(chunked, &FormatOptions::default()),
+            "HTTP/1.1 200 OK\nContent-Type: application/json\n\n{\n  \"a\": 1\n}"
+        );
+        // A body that cannot be decoded is kept as written, and so are its headers.
+        let gzipped = "HTTP/1.1 200 OK\ncontent-encoding: gzip\nContent-Length: 3\n\n[1]";
+        assert_eq!(format_http(gzipped, &FormatOptions::default()), gzipped);
+    }
+
+    #[test]
+    fn looks_like_http_needs_a_start_line() {
+        assert!(looks_like_http("GET / HTTP/1.1\nHost: x"));
+        assert!(looks_like_http("HTTP/2 404 \nserver: nginx"));
+        assert!(!looks_like_http("GET the data from the server"));
+        assert!(!looks_like_http("Content-Type: application/json"));
+    }
+}
@@ -0,0 +1,280 @@
+//! INI files: `[section]` headers with `key=value` (or `key: value`) entries.
+
+use serde::Serialize;
+
+use crate::FormatOptions;
+
+/// A key that appears more than once in the same section (1-based lines of every occurrence).
+#[derive(Debug, Serialize)]
+pub struct DuplicateKey {
+    /// Section name; empty for keys before the first header.
+    pub section: String,
+    pub key: String,
+    pub lines: Vec<u32>,
+}
+
+/// One `key=value` entry with the comment lines directly above it and any continuation lines.
+#[derive(Debug)]
+struct Entry {
+    comments: Vec<String>,
+    /// Leading whitespace of the entry's line, in bytes.
+    indent: usize,
+    key: String,
+    /// `=` or `:`; `None` for a bare key with no value.
+    separator: Option<char>,
+    value: String,
+    continuation: Vec<String>,
+    line: u32,
+}
+
+#[derive(Debug, Default)]
+struct Section {
+    /// `None` for entries before the first header.
+    name: Option<String>,
+    comments: Vec<String>,
+    entries: Vec<Entry>,
+    /// Comments after the last entry, not attached to any key.
+    trailing: Vec<String>,
+}
+
+fn is_comment(t: &str) -> bool {
+    t.starts_with(';') || t.starts_with('#')
+}
+
+fn section_header(t: &str) -> Option<&str> {
+    t.strip_prefix('[')?.strip_suffix(']').map(str::trim)
+}
+
+/// True for a `[section]` header line that isn't also a JSON array: `[remote "origin"]` but not
+/// `["a", "b"]`.
+pub fn is_section_header(line: &str) -> bool {
+    let t = line.trim();
+    section_header(t).is_some_and(|name| !name.is_empty())
+        && serde_json::from_str::<serde::de::IgnoredAny>(t).is_err()
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Split `key=value` / `key: value` at the first separator.
+fn split_entry(t: &str) -> Option<(&str, char, &str)> {
+    let i = t.find(['=', ':'])?;
+    let key = t[..i].trim();
+    if key.is_empty() {
+        return None;
+    }
+    Some((key, t[i..].chars().next()?, t[i + 1..].trim()))
+}
+
+fn parse(content: &str) -> Vec<Section> {
+    let mut sections = vec![Section::default()];
+    let mut pending: Vec<String> = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let t = line.trim();
+        let section = sections.last_mut().expect("always at least one section");
+        if t.is_empty() {
+            continue;
+        }
+        if is_comment(t) {
+            pending.push(t.to_string());
+        } else if let Some(name) = section_header(t) {
+            sections.push(Section {
+                name: Some(name.to_string()),
+                comments: std::mem::take(&mut pending),
+                ..Section::default()
+            });
+        } else if let Some(entry) = section.entries.last_mut().filter(|e| {
+            // Indented keys (gitconfig, systemd) are entries; a continuation has no separator
+            // or is indented deeper than its entry.
+            pending.is_empty()
+                && indent_of(line) > 0
+                && (split_entry(t).is_none() || indent_of(line) > e.indent)
+        }) {
+            entry.continuation.push(line.to_string());
+        } else if let Some((key, separator, value)) = split_entry(t) {
+            section.entries.push(Entry {
+                comments: std::mem::take(&mut pending),
+                indent: indent_of(line),
+                key: key.to_string(),
+                separator: Some(separator),
+                value: value.to_string(),
+                continuation: Vec::new(),
+                line: (i + 1) as u32,
+            });
+        } else {
+            // A bare key with no value (allowed by some INI dialects).
+            section.entries.push(Entry {
+                comments: std::mem::take(&mut pending),
+                indent: indent_of(line),
+                key: t.to_string(),
+                separator: None,
+                value: String::new(),
+                continuation: Vec::new(),
+                line: (i + 1) as u32,
+            });
+        }
+    }
+    sections.last_mut().unwrap().trailing.append(&mut pending);
+    sections
+}
+
+/// True if the content has at least one `[section]` header and one entry, and every other line
+/// is a comment, an entry (indented or not) or an indented continuation.
+pub fn looks_like_ini(content: &str) -> bool {
+    let mut headers = 0;
+    let mut entries = 0;
+    for line in content.lines() {
+        let t = line.trim();
+        if t.is_empty() || is_comment(t) {
+            continue;
+        }
+        if section_header(t).is_some() {
+            headers += 1;
+        } else if split_entry(t).is_some() {
+            entries += 1;
+        } else if indent_of(line) == 0 {
+            return false;
+        }
+    }
+    headers > 0 && entries > 0
+}
+
+/// Normalize an INI file section by section: `key = value` spacing, one blank line between
+/// sections. With `sort_keys`, keys are sorted within each section, taking their comments along;
+/// sections themselves keep their order.
+pub fn format_ini(content: &str, sort_keys: bool) -> String {
+    let mut out: Vec<String> = Vec::new();
+    for mut section in parse(content) {
+        if section.name.is_none() && section.entries.is_empty() && section.trailing.is_empty() {
+            continue;
+        }
+        if !out.is_empty() {
+            out.push(String::new());
+        }
+        out.append(&mut section.comments);
+        if let Some(name) = &section.name {
+            out.push(format!("[{}]", name));
+        }
+        if sort_keys {
+            section.entries.sort_by_key(|e| e.key.to_lowercase());
+        }
+        for mut entry in section.entries {
+            out.append(&mut entry.comments);
+            out.push(match entry.separator {
+                Some(':') => format!("{}: {}", entry.key, entry.value),
+                Some(_) => format!("{} = {}", entry.key, entry.value),
+                None => entry.key,
+            });
+            out.append(&mut entry.continuation);
+        }
+        out.append(&mut section.trailing);
+    }
+    out.iter()
+        .map(|l| l.trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Normalize an INI file; keys are sorted within their section only when `sort_keys` is set.
+/// `trailing_newline` is honoured too.
+#[tauri::command]
+pub fn format_ini_sections(content: String, options: Option<FormatOptions>) -> String {
+    let options = options.unwrap_or_default();
+    options.finish(format_ini(&content, options.sort_keys))
+}
+
+/// Report keys defined more than once within the same section (compared case-insensitively).
+#[tauri::command]
+pub fn ini_duplicate_keys(content: String) -> Vec<DuplicateKey> {
+    let mut duplicates = Vec::new();
+    for section in parse(&content) {
+        let mut seen: Vec<(String, Vec<u32>)> = Vec::new();
+        for entry in &section.entries {
+            let key = entry.key.to_lowercase();
+            match seen.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, lines)) => lines.push(entry.line),
+                None => seen.push((key, vec![entry.line])),
+            }
+        }
+        for (_, lines) in seen.into_iter().filter(|(_, lines)| lines.len() > 1) {
+            let first = section.entries.iter().find(|e| e.line == lines[0]).unwrap();
+            duplicates.push(DuplicateKey {
+                section: section.name.clone().unwrap_or_default(),
+                key: first.key.clone(),
+                lines,
+            });
+        }
+    }
+    duplicates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "; global\nroot=1\n\n[service]\n; the binary\nPath = C:\\bin\\svc.exe\nargs=--fast\n\n# db section\n[db]\nuser: admin\nhost=localhost\n  backup.local\n";
+
+    #[test]
+    fn looks_like_ini_needs_sections_and_entries() {
+        assert!(looks_like_ini(SAMPLE));
+        assert!(!looks_like_ini("key=value\nother=1"));
+        assert!(!looks_like_ini("[section]\nsome free text here"));
+    }
+
+    #[test]
+    fn format_ini_keeps_bare_keys() {
+        assert_eq!(
+            format_ini("[mysqld]\nskip-networking\nport=3306", false),
+            "[mysqld]\nskip-networking\nport = 3306"
+        );
+    }
+
+    #[test]
+    fn indented_keys_are_entries_not_continuations() {
+        let dups = ini_duplicate_keys("[a]\n  k = 1\n  k = 2\n".to_string());
+        assert_eq!(dups.len(), 1);
+        assert_eq!(dups[0].lines, vec![2, 3]);
+        let gitconfig = "[core]\n\tbare = false\n\tfilemode = true\n[remote \"origin\"]\n\turl = git@host:repo.git\n\t\tmirror";
+        assert!(looks_like_ini(gitconfig));
+        assert_eq!(
+            format_ini(gitconfig, false),
+            "[core]\nbare = false\nfilemode = true\n\n[remote \"origin\"]\nurl = git@host:repo.git\n\t\tmirror"
+        );
+        assert!(is_section_header("[remote \"origin\"]"));
+        assert!(!is_section_header("[\"a\", \"b\"]"));
+    }
+
+    #[test]
+    fn format_ini_keeps_section_membership() {
+        let out = format_ini(SAMPLE, false);
+        assert_eq!(
+            out,
+            "; global\nroot = 1\n\n[service]\n; the binary\nPath = C:\\bin\\svc.exe\nargs = --fast\n\n# db section\n[db]\nuser: admin\nhost = localhost\n  backup.local"
+        );
+    }
+
+    #[test]
+    fn format_ini_sorts_within_sections_with_comments() {
+        let out = format_ini("[b]\nz=1\n; about a\na=2\n[a]\ny=3", true);
+        assert_eq!(out, "[b]\n; about a\na = 2\nz = 1\n\n[a]\ny = 3");
+        let options = FormatOptions {
+            sort_keys: true,
+            trailing_newline: true,
+            ..Default::default()
+        };
+        let out = format_ini_sections("[b]\nz=1\na=2".to_string(), Some(options));
+        assert_eq!(out, "[b]\na = 2\nz = 1\n");
+    }
+
+    #[test]
+    fn ini_duplicate_keys_reports_per_section() {
+        let dups = ini_duplicate_keys("[a]\nx=1\nX=2\ny=1\n[b]\nx=3\ny=4\ny=5".to_string());
+        assert_eq!(dups.len(), 2);
+        assert_eq!(dups[0].section, "a");
+        assert_eq!(dups[0].key, "x");
+        assert_eq!(dups[0].lines, vec![2, 3]);
+        assert_eq!(dups[1].section, "b");
+        assert_eq!(dups[1].lines, vec![7, 8]);
+    }
+}
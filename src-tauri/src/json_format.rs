@@ -0,0 +1,296 @@
+//! JSON printing that keeps the document as written: key order, duplicate keys, number text
+//! (`12345678901234567890`, `0.10`) and string escapes are carried over verbatim.
+
+/// A parsed JSON value holding the source text of its scalars and keys.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    /// A string, number, `true`, `false` or `null`, exactly as written.
+    Scalar(String),
+    Array(Vec<Json>),
+    /// Members in source order; keys are kept as their quoted literal.
+    Object(Vec<(String, Json)>),
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    text: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|b| b.is_ascii_whitespace())
+        {
+            self.pos += 1;
+        }
+    }
+
+    /// The string literal starting at the current `"`, quotes included.
+    fn string(&mut self) -> String {
+        let start = self.pos;
+        self.pos += 1;
+        while let Some(&b) = self.bytes.get(self.pos) {
+            self.pos += if b == b'\\' { 2 } else { 1 };
+            if b == b'"' {
+                break;
+            }
+        }
+        self.text[start..self.pos].to_string()
+    }
+
+    fn value(&mut self) -> Json {
+        self.skip_whitespace();
+        match self.bytes.get(self.pos) {
+            Some(b'{') => {
+                self.pos += 1;
+                let mut members = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    match self.bytes.get(self.pos) {
+                        Some(b'}') | None => break,
+                        Some(b',') => self.pos += 1,
+                        Some(_) => {
+                            let key = self.string();
+                            self.skip_whitespace();
+                            self.pos += 1;
+                            members.push((key, self.value()));
+                        }
+                    }
+                }
+                self.pos += 1;
+                Json::Object(members)
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    match self.bytes.get(self.pos) {
+                        Some(b']') | None => break,
+                        Some(b',') => self.pos += 1,
+                        Some(_) => items.push(self.value()),
+                    }
+                }
+                self.pos += 1;
+                Json::Array(items)
+            }
+            Some(b'"') => Json::Scalar(self.string()),
+            _ => {
+                let start = self.pos;
+                while self
+                    .bytes
+                    .get(self.pos)
+                    .is_some_and(|b| !b.is_ascii_whitespace() && !b",]}".contains(b))
+                {
+                    self.pos += 1;
+                }
+                Json::Scalar(self.text[start..self.pos].to_string())
+            }
+        }
+    }
+}
+
+/// Parse JSON text, keeping its source form. Validation (and the error message) is serde_json's.
+pub fn parse(text: &str) -> Result<Json, String> {
+    serde_json::from_str::<serde::de::IgnoredAny>(text).map_err(|e| e.to_string())?;
+    let mut parser = Parser {
+        bytes: text.as_bytes(),
+        text,
+        pos: 0,
+    };
+    Ok(parser.value())
+}
+
+/// Sort object members by their decoded key at every level; equal keys keep their order.
+pub fn sort_keys(json: &mut Json) {
+    match json {
+        Json::Scalar(_) => {}
+        Json::Array(items) => items.iter_mut().for_each(sort_keys),
+        Json::Object(members) => {
+            members.sort_by_cached_key(|(key, _)| {
+                serde_json::from_str::<String>(key).unwrap_or_else(|_| key.clone())
+            });
+            members.iter_mut().for_each(|(_, value)| sort_keys(value));
+        }
+    }
+}
+
+struct Printer<'a> {
+    indent: &'a str,
+    max_width: usize,
+    out: String,
+}
+
+impl Printer<'_> {
+    fn newline(&mut self, depth: usize) {
+        self.out.push('\n');
+        self.out.extend(std::iter::repeat_n(self.indent, depth));
+    }
+
+    /// Column of the next character, counted in characters from the start of the line.
+    fn column(&self) -> usize {
+        let line = self.out.rsplit('\n').next().unwrap_or_default();
+        line.chars().count()
+    }
+
+    /// An array of scalars on one line, if that line (with a trailing comma) fits in `max_width`.
+    fn inline(&self, items: &[Json]) -> Option<String> {
+        if self.max_width == 0 {
+            return None;
+        }
+        let scalars = items
+            .iter()
+            .map(|item| match item {
+                Json::Scalar(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let line = format!("[{}]", scalars.join(", "));
+        (self.column() + line.chars().count() < self.max_width).then_some(line)
+    }
+
+    fn value(&mut self, json: &Json, depth: usize) {
+        match json {
+            Json::Scalar(text) => self.out.push_str(text),
+            Json::Array(items) if items.is_empty() => self.out.push_str("[]"),
+            Json::Object(members) if members.is_empty() => self.out.push_str("{}"),
+            Json::Array(items) => {
+                if let Some(line) = self.inline(items) {
+                    self.out.push_str(&line);
+                    return;
+                }
+                self.out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push(',');
+                    }
+                    self.newline(depth + 1);
+                    self.value(item, depth + 1);
+                }
+                self.newline(depth);
+                self.out.push(']');
+            }
+            Json::Object(members) => {
+                self.out.push('{');
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push(',');
+                    }
+                    self.newline(depth + 1);
+                    self.out.push_str(key);
+                    self.out.push_str(": ");
+                    self.value(value, depth + 1);
+                }
+                self.newline(depth);
+                self.out.push('}');
+            }
+        }
+    }
+}
+
+/// Pretty-print one member or item per line, indenting each level by `indent`. With a non-zero
+/// `max_width`, arrays of scalars that fit within it stay on one line.
+pub fn pretty(json: &Json, indent: &str, max_width: usize) -> String {
+    let mut printer = Printer {
+        indent,
+        max_width,
+        out: String::new(),
+    };
+    printer.value(json, 0);
+    printer.out
+}
+
+fn write_compact(json: &Json, out: &mut String) {
+    match json {
+        Json::Scalar(text) => out.push_str(text),
+        Json::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_compact(item, out);
+            }
+            out.push(']');
+        }
+        Json::Object(members) => {
+            out.push('{');
+            for (i, (key, value)) in members.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(key);
+                out.push(':');
+                write_compact(value, out);
+            }
+            out.push('}');
+        }
+    }
+}
+
+/// Print on one line with no whitespace between tokens.
+pub fn compact(json: &Json) -> String {
+    let mut out = String::new();
+    write_compact(json, &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pretty_keeps_key_order_and_number_text() {
+        let text = r#"{"zeta":12345678901234567890,"alpha":[0.10,1e3,-0.0],"mid":{}}"#;
+        assert_eq!(
+            pretty(&parse(text).unwrap(), "  ", 0),
+            "{\n  \"zeta\": 12345678901234567890,\n  \"alpha\": [\n    0.10,\n    1e3,\n    -0.0\n  ],\n  \"mid\": {}\n}"
+        );
+    }
+
+    #[test]
+    fn pretty_keeps_short_scalar_arrays_on_one_line() {
+        let json =
+            parse(r#"{"ports":[80,443],"hosts":["alpha.example","beta.example"],"m":[[1]]}"#)
+                .unwrap();
+        assert_eq!(
+            pretty(&json, "\t", 30),
+            "{\n\t\"ports\": [80, 443],\n\t\"hosts\": [\n\t\t\"alpha.example\",\n\t\t\"beta.example\"\n\t],\n\t\"m\": [\n\t\t[1]\n\t]\n}"
+        );
+    }
+
+    #[test]
+    fn compact_drops_whitespace_between_tokens_only() {
+        let json = parse("{\n  \"a b\": [ 1 , 2.50 ],\n  \"c\": { }\n}").unwrap();
+        assert_eq!(compact(&json), r#"{"a b":[1,2.50],"c":{}}"#);
+    }
+
+    #[test]
+    fn strings_keep_escapes_and_structural_characters() {
+        let text = " [ \"a, \\\"b\\\" ]}\" , \"caf\\u00e9\\\\\" , true,null ] ";
+        assert_eq!(
+            parse(text).unwrap(),
+            Json::Array(vec![
+                Json::Scalar(r#""a, \"b\" ]}""#.to_string()),
+                Json::Scalar(r#""caf\u00e9\\""#.to_string()),
+                Json::Scalar("true".to_string()),
+                Json::Scalar("null".to_string()),
+            ])
+        );
+        assert!(parse("{\"a\": 1,}").is_err());
+        assert!(parse("[1] 2").is_err());
+    }
+
+    #[test]
+    fn sort_keys_orders_every_level_by_decoded_key() {
+        let mut json = parse(r#"{"b":{"y":1,"x":2},"\u0061":[{"d":0,"c":0}],"b":0}"#).unwrap();
+        sort_keys(&mut json);
+        assert_eq!(
+            pretty(&json, "  ", 0),
+            "{\n  \"\\u0061\": [\n    {\n      \"c\": 0,\n      \"d\": 0\n    }\n  ],\n  \"b\": {\n    \"x\": 2,\n    \"y\": 1\n  },\n  \"b\": 0\n}"
+        );
+    }
+}
@@ -0,0 +1,220 @@
+//! Stringified JSON: JSON encoded as a JSON string (`"{\"a\":1}"`), possibly escaped more than once,
+//! as found in logs and API payloads.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::json_format::{self, Json};
+
+/// Deepest level of string-in-string escaping unwrapped.
+const MAX_DEPTH: usize = 8;
+
+/// A string literal on a line whose content is JSON.
+#[derive(Debug, Serialize)]
+pub struct StringifiedJson {
+    /// 1-based line number.
+    pub line: usize,
+    /// Byte offset of the opening quote within the line.
+    pub start: usize,
+    /// Byte offset just past the closing quote.
+    pub end: usize,
+    /// How many times the JSON was escaped.
+    pub depth: usize,
+    /// The unescaped JSON, pretty-printed.
+    pub value: String,
+}
+
+/// Parse the content of a JSON string: either JSON itself, or (when escaped again) the body of
+/// another string literal.
+fn parse_inner(s: &str) -> Option<Value> {
+    let t = s.trim();
+    serde_json::from_str(t).ok().or_else(|| {
+        serde_json::from_str::<String>(&format!("\"{}\"", t))
+            .ok()
+            .map(Value::String)
+    })
+}
+
+/// Unwrap a string holding stringified JSON until an object or array is reached. Returns it with
+/// the number of levels unwrapped and its JSON text, or `None` if the string doesn't hold JSON.
+fn unwrap_string(s: &str) -> Option<(Value, usize, String)> {
+    let mut current = s.to_string();
+    for depth in 1..=MAX_DEPTH {
+        let t = current.trim();
+        if !(t.starts_with('{') || t.starts_with('[') || t.starts_with('"')) {
+            return None;
+        }
+        match parse_inner(t)? {
+            Value::String(inner) if inner != current => current = inner,
+            value @ (Value::Object(_) | Value::Array(_)) => {
+                return Some((value, depth, t.to_string()))
+            }
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Parse text that is stringified JSON: a JSON string literal holding JSON, or the escaped body of
+/// one without its quotes (`{\"a\":1}`). Returns the JSON with its escaping depth.
+pub fn parse_stringified(text: &str) -> Option<(Value, usize)> {
+    unwrap_stringified(text).map(|(value, depth, _)| (value, depth))
+}
+
+/// The JSON text held by stringified JSON, unescaped but otherwise as written.
+pub fn stringified_body(text: &str) -> Option<String> {
+    unwrap_stringified(text).map(|(_, _, body)| body)
+}
+
+fn unwrap_stringified(text: &str) -> Option<(Value, usize, String)> {
+    let t = text.trim();
+    match serde_json::from_str::<Value>(t) {
+        Ok(Value::String(s)) => unwrap_string(&s),
+        Ok(_) => None,
+        Err(_) if t.contains("\\\"") => {
+            unwrap_string(t).map(|(value, depth, body)| (value, depth - 1, body))
+        }
+        Err(_) => None,
+    }
+}
+
+/// True if the text is a JSON string literal (or its escaped body) holding JSON.
+pub fn looks_like_stringified_json(text: &str) -> bool {
+    parse_stringified(text).is_some()
+}
+
+/// Replace string values holding JSON, at any level, by the JSON they hold.
+fn expand(json: Json) -> Json {
+    match json {
+        Json::Scalar(text) => {
+            let inner = serde_json::from_str::<String>(&text)
+                .ok()
+                .and_then(|s| unwrap_string(&s))
+                .and_then(|(_, _, body)| json_format::parse(&body).ok());
+            match inner {
+                Some(inner) => expand(inner),
+                None => Json::Scalar(text),
+            }
+        }
+        Json::Array(items) => Json::Array(items.into_iter().map(expand).collect()),
+        Json::Object(members) => {
+            Json::Object(members.into_iter().map(|(k, v)| (k, expand(v))).collect())
+        }
+    }
+}
+
+/// End (exclusive) of the JSON string literal starting at `start`, a `"` in `line`.
+fn literal_end(line: &[u8], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    while i < line.len() {
+        match line[i] {
+            b'\\' => i += 2,
+            b'"' => return Some(i + 1),
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// Unescape stringified JSON (however many times it was escaped), along with any string values
+/// inside it that hold JSON themselves, and pretty-print the result. Plain JSON is accepted too.
+#[tauri::command]
+pub fn unescape_json(content: String) -> Result<String, String> {
+    let text = stringified_body(&content).unwrap_or_else(|| content.trim().to_string());
+    let json = json_format::parse(&text)?;
+    Ok(json_format::pretty(&expand(json), "  ", 0))
+}
+
+/// Escape content as a JSON string literal. With `compact`, valid JSON is minified first.
+#[tauri::command]
+pub fn escape_json_string(content: String, compact: Option<bool>) -> String {
+    let text = match json_format::parse(&content) {
+        Ok(json) if compact.unwrap_or(false) => json_format::compact(&json),
+        _ => content,
+    };
+    Value::String(text).to_string()
+}
+
+/// Find string literals holding JSON, line by line, with their position and unescaped value.
+#[tauri::command]
+pub fn stringified_json(content: String) -> Vec<StringifiedJson> {
+    let mut found = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let bytes = line.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] != b'"' {
+                i += 1;
+                continue;
+            }
+            let Some(end) = literal_end(bytes, i) else {
+                break;
+            };
+            if let Some((_, depth, body)) = unwrap_stringified(&line[i..end]) {
+                found.push(StringifiedJson {
+                    line: index + 1,
+                    start: i,
+                    end,
+                    depth,
+                    value: json_format::parse(&body)
+                        .map(|json| json_format::pretty(&json, "  ", 0))
+                        .unwrap_or(body),
+                });
+            }
+            i = end;
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_stringified_unwraps_each_level_of_escaping() {
+        let (value, depth) = parse_stringified(r#""{\"a\":1}""#).unwrap();
+        assert_eq!((value.to_string(), depth), (r#"{"a":1}"#.to_string(), 1));
+        let (value, depth) = parse_stringified(r#""{\\\"a\\\":[1,2]}""#).unwrap();
+        assert_eq!(
+            (value.to_string(), depth),
+            (r#"{"a":[1,2]}"#.to_string(), 2)
+        );
+        let (_, depth) = parse_stringified(r#"{\"a\":1}"#).unwrap();
+        assert_eq!(depth, 1);
+        assert!(parse_stringified(r#""plain text""#).is_none());
+        assert!(parse_stringified(r#"{"a":1}"#).is_none());
+    }
+
+    #[test]
+    fn unescape_json_expands_nested_strings_and_escape_reverses_it() {
+        let out =
+            unescape_json(r#""{\"event\":\"created\",\"body\":\"{\\\"id\\\":7}\"}""#.to_string())
+                .unwrap();
+        assert_eq!(
+            out,
+            "{\n  \"event\": \"created\",\n  \"body\": {\n    \"id\": 7\n  }\n}"
+        );
+        let escaped = escape_json_string("{\n  \"id\": 7\n}".to_string(), Some(true));
+        assert_eq!(escaped, r#""{\"id\":7}""#);
+        assert_eq!(unescape_json(escaped).unwrap(), "{\n  \"id\": 7\n}");
+        assert_eq!(
+            escape_json_string("say \"hi\"".to_string(), None),
+            r#""say \"hi\"""#
+        );
+    }
+
+    #[test]
+    fn stringified_json_finds_literals_in_log_lines() {
+        let log = "2024-05-01 INFO sent\n{\"level\":\"info\",\"payload\":\"{\\\"ok\\\":true}\"}";
+        let found = stringified_json(log.to_string());
+        assert_eq!(found.len(), 1);
+        let f = &found[0];
+        assert_eq!((f.line, f.depth), (2, 1));
+        assert_eq!(
+            &log.lines().nth(1).unwrap()[f.start..f.end],
+            "\"{\\\"ok\\\":true}\""
+        );
+        assert_eq!(f.value, "{\n  \"ok\": true\n}");
+    }
+}
@@ -1,15 +1,47 @@
 //! SiftView backend: file I/O, content detection, diff.
 
-use std::io::Cursor;
+use std::io::{Cursor, Read};
 
 use serde::{Deserialize, Serialize};
 use similar::{TextDiff, DiffOp};
 
+mod binary;
+mod columnar;
+mod csv_dialect;
+mod decode;
+mod email;
+mod html;
+mod http;
+mod ini;
+mod json_format;
+mod json_string;
+mod markdown;
+mod ndjson;
+mod pem;
+mod protobuf;
+mod serialized;
+mod spreadsheet;
+mod sql;
+mod sqlite;
+mod stacktrace;
+mod timestamps;
+mod toml;
+mod yaml;
+
+use binary::BinaryInfo;
+use csv_dialect::CsvDialect;
+
 /// Result of content detection for a buffer or segment.
 #[derive(Debug, Serialize)]
 pub struct DetectedType {
     pub kind: String,
     pub confidence: f64,
+    /// Sniffed delimiter, quoting and header presence when `kind` is `csv`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dialect: Option<CsvDialect>,
+    /// Format and metadata when the file is a recognized binary format (`kind` is then its kind).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub binary: Option<BinaryInfo>,
 }
 
 /// A contiguous region of the buffer with a detected content type (1-based inclusive lines).
@@ -18,10 +50,109 @@ pub struct Segment {
     pub start_line: u32,
     pub end_line: u32,
     pub kind: String,
+    /// Short description of the segment, e.g. `Deployment/web` for a YAML document.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Sniffed dialect of a `csv` segment (from the extension for `.tsv`), used again when formatting.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dialect: Option<CsvDialect>,
+}
+
+/// Output style for the formatters. Missing fields take their defaults: each format's usual
+/// indentation, keys in source order, no width limit, no trailing newline, attributes on the tag's
+/// line. Indentation applies to JSON, NDJSON, XML, HTML, SQL, TOML and YAML; INI, properties, CSV
+/// and HTTP headers have none.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct FormatOptions {
+    /// Spaces per nesting level; unset, two (four for TOML's multi-line arrays and tables).
+    pub indent: Option<usize>,
+    /// Indent with one tab per level instead of spaces. Not for YAML, which forbids tabs there.
+    pub use_tabs: bool,
+    /// Sort JSON and NDJSON object keys, and INI keys within their section. YAML and TOML keep
+    /// their key order, as they are formatted line by line to keep comments.
+    pub sort_keys: bool,
+    /// Maximum line width, 0 for none: JSON arrays of scalars that fit stay on one line, and XML
+    /// tags that don't fit get their attributes wrapped when `wrap_attributes` is set.
+    pub max_width: usize,
+    /// End the output with a newline.
+    pub trailing_newline: bool,
+    /// Put each attribute of an XML tag with several attributes on its own line.
+    pub wrap_attributes: bool,
+    /// Minify instead of pretty-printing: JSON on one line (NDJSON one line per record), XML
+    /// without whitespace between tags, YAML in flow style. Other kinds are left unchanged.
+    pub compact: bool,
+}
+
+impl FormatOptions {
+    /// Spaces per level, `default` when not set.
+    fn indent_width(&self, default: usize) -> usize {
+        self.indent.unwrap_or(default)
+    }
+
+    /// Text of one indentation level, `default` spaces when not set.
+    fn indent_unit(&self, default: usize) -> String {
+        if self.use_tabs {
+            "\t".to_string()
+        } else {
+            " ".repeat(self.indent_width(default))
+        }
+    }
+
+    /// Apply `trailing_newline` to formatted output.
+    fn finish(&self, mut out: String) -> String {
+        if self.trailing_newline && !out.ends_with('\n') {
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// A parse error located in the buffer (1-based line and column).
+#[derive(Debug, Clone, Serialize)]
+pub struct ParseError {
+    pub line: u32,
+    pub column: u32,
+    pub message: String,
+}
+
+impl ParseError {
+    /// Convert a serde_json error, shifting its line by the number of buffer lines before the parsed text.
+    fn from_json(e: &serde_json::Error, line_offset: u32) -> Self {
+        let position = format!(" at line {} column {}", e.line(), e.column());
+        let message = e.to_string();
+        ParseError {
+            line: e.line() as u32 + line_offset,
+            column: e.column() as u32,
+            message: message.strip_suffix(&position).unwrap_or(&message).to_string(),
+        }
+    }
+
+    /// Locate a byte offset in `content` as a 1-based line and column (in characters).
+    fn at_offset(content: &str, offset: usize, message: &str) -> Self {
+        let before = content.get(..offset).unwrap_or(content);
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        ParseError {
+            line: before.matches('\n').count() as u32 + 1,
+            column: before[line_start..].chars().count() as u32 + 1,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// An entry in a document outline, e.g. a Markdown heading (1-based inclusive lines).
+#[derive(Debug, Serialize)]
+pub struct OutlineItem {
+    pub level: u8,
+    pub title: String,
+    pub start_line: u32,
+    pub end_line: u32,
 }
 
 /// Max file size to read (5 MB). Larger files return an error to avoid freezing the app.
 const MAX_FILE_SIZE_BYTES: u64 = 5 * 1024 * 1024;
+/// Leading bytes read by `detect_content` to recognize a binary format.
+const SIGNATURE_BYTES: u64 = 64 * 1024;
 
 // Tauri commands: do not use `pub` on command fns when they live in the same file as
 // `generate_handler![]` — it causes duplicate `__cmd__*` macro definitions at compile time.
@@ -39,9 +170,11 @@ async fn read_file(path: String) -> Result<String, String> {
             mb, max_mb
         ));
     }
-    tokio::fs::read_to_string(&path)
-        .await
-        .map_err(|e| e.to_string())
+    let bytes = tokio::fs::read(&path).await.map_err(|e| e.to_string())?;
+    String::from_utf8(bytes).map_err(|e| match binary::identify(e.as_bytes()) {
+        Some(info) => format!("{} is a binary file and can't be opened as text.", info.description),
+        None => "stream did not contain valid UTF-8".to_string(),
+    })
 }
 
 /// Write content to the given path. Used for Save and Save As.
@@ -52,25 +185,83 @@ async fn write_file(path: String, content: String) -> Result<(), String> {
         .map_err(|e| e.to_string())
 }
 
-/// Detect content type from raw text and optional file extension.
+/// Detect content type from raw text and optional file extension. With the file's `path`, known binary
+/// formats (images, PDF, archives, SQLite, ELF, Parquet, ...) are recognized from their leading bytes;
+/// `binary_info` reads the whole file for metadata such as PDF pages or ZIP entries.
 #[tauri::command]
-fn detect_content(content: &str, extension: Option<String>) -> DetectedType {
+fn detect_content(content: &str, extension: Option<String>, path: Option<String>) -> DetectedType {
     let ext = extension.as_deref().unwrap_or("");
+    let bytes = path.and_then(|p| {
+        let mut bytes = Vec::new();
+        let file = std::fs::File::open(p).ok()?;
+        file.take(SIGNATURE_BYTES).read_to_end(&mut bytes).ok()?;
+        Some(bytes)
+    });
+    if let Some(info) = bytes.as_deref().and_then(binary::identify) {
+        return DetectedType {
+            kind: info.kind.clone(),
+            confidence: 0.99,
+            dialect: None,
+            binary: Some(info),
+        };
+    }
     // Extension-based detection first
-    let (kind, confidence) = match ext.to_lowercase().as_str() {
-        "json" => ("json", 0.95),
-        "csv" => ("csv", 0.95),
-        "xml" | "html" => ("xml", 0.9),
-        "yaml" | "yml" => ("yaml", 0.95),
-        "env" | "properties" => ("properties", 0.9),
-        _ => content_detection_heuristic(content),
-    };
+    let (kind, confidence) =
+        kind_from_extension(ext).unwrap_or_else(|| content_detection_heuristic(content));
     DetectedType {
         kind: kind.to_string(),
         confidence,
+        dialect: (kind == "csv").then(|| csv_dialect::sniff(content, ext)),
+        binary: None,
     }
 }
 
+/// Identify a binary file from all of its bytes (up to MAX_FILE_SIZE_BYTES), with the metadata
+/// that needs the whole file: PDF page count, ZIP entries, SQLite tables. `None` if the format
+/// isn't recognized.
+#[tauri::command]
+async fn binary_info(path: String) -> Result<Option<BinaryInfo>, String> {
+    tokio::task::spawn_blocking(move || {
+        let mut bytes = Vec::new();
+        std::fs::File::open(&path)
+            .and_then(|file| file.take(MAX_FILE_SIZE_BYTES).read_to_end(&mut bytes))
+            .map_err(|e| e.to_string())?;
+        Ok(binary::identify(&bytes))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Content kind and confidence implied by a file extension, if it is one we recognize.
+fn kind_from_extension(ext: &str) -> Option<(&'static str, f64)> {
+    let kind = match ext.to_lowercase().as_str() {
+        "json" => ("json", 0.95),
+        "jsonl" | "ndjson" => ("ndjson", 0.95),
+        "csv" | "tsv" | "tab" => ("csv", 0.95),
+        "xml" => ("xml", 0.9),
+        "html" | "htm" => ("html", 0.9),
+        "yaml" | "yml" => ("yaml", 0.95),
+        "env" | "properties" => ("properties", 0.9),
+        "toml" => ("toml", 0.95),
+        "ini" | "cfg" => ("ini", 0.9),
+        "md" | "markdown" => ("markdown", 0.95),
+        "sql" => ("sql", 0.95),
+        "http" => ("http", 0.9),
+        "eml" => ("email", 0.95),
+        "db" | "sqlite" | "sqlite3" => ("sqlite", 0.9),
+        "parquet" => ("parquet", 0.95),
+        "arrow" | "arrows" | "feather" | "ipc" => ("arrow", 0.9),
+        "xlsx" | "xlsm" | "xlsb" | "xls" | "ods" => ("spreadsheet", 0.95),
+        "msgpack" | "mpk" => ("msgpack", 0.95),
+        "cbor" => ("cbor", 0.95),
+        "bson" => ("bson", 0.95),
+        "pb" | "binpb" | "protobuf" => ("protobuf", 0.8),
+        "pem" | "crt" | "cer" | "csr" | "key" => ("pem", 0.9),
+        _ => return None,
+    };
+    Some(kind)
+}
+
 /// Block in a structured diff: either unchanged lines (collapsible) or a changed region.
 #[derive(Debug, Serialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -150,17 +341,41 @@ fn compute_diff(left: String, right: String) -> String {
     )
 }
 
-/// Pretty-print JSON. Returns an error if content is not valid JSON.
+/// Pretty-print JSON, keeping key order and number text as written; keys are sorted only when
+/// `sort_keys` is set in the options. Stringified JSON (a string literal holding JSON, possibly
+/// escaped more than once) is unescaped first. Returns an error if content is not valid JSON.
 #[tauri::command]
-fn format_json(content: String) -> Result<String, String> {
-    let value: serde_json::Value =
-        serde_json::from_str(&content).map_err(|e| e.to_string())?;
-    serde_json::to_string_pretty(&value).map_err(|e| e.to_string())
+fn format_json(content: String, options: Option<FormatOptions>) -> Result<String, String> {
+    let options = options.unwrap_or_default();
+    Ok(options.finish(render_json(&content, &options)?))
+}
+
+fn render_json(content: &str, options: &FormatOptions) -> Result<String, String> {
+    let text = json_string::stringified_body(content).unwrap_or_else(|| content.to_string());
+    Ok(print_json(json_format::parse(&text)?, options))
+}
+
+/// Print parsed JSON in the style set by `options`.
+fn print_json(mut json: json_format::Json, options: &FormatOptions) -> String {
+    if options.sort_keys {
+        json_format::sort_keys(&mut json);
+    }
+    if options.compact {
+        return json_format::compact(&json);
+    }
+    json_format::pretty(&json, &options.indent_unit(2), options.max_width)
 }
 
-/// Parse CSV and re-output with aligned columns. Fails on parse error.
-fn format_csv(content: &str) -> Result<String, String> {
-    let mut reader = csv::Reader::from_reader(Cursor::new(content.as_bytes()));
+/// Parse CSV in the given dialect and re-output with aligned columns. Fails on parse error.
+fn format_csv(content: &str, dialect: &CsvDialect) -> Result<String, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(dialect.delimiter as u8)
+        .quote(dialect.quote as u8)
+        .double_quote(!dialect.backslash_escape)
+        .escape(dialect.backslash_escape.then_some(b'\\'))
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(Cursor::new(content.as_bytes()));
     let rows: Vec<Vec<String>> = reader
         .records()
         .map(|r| {
@@ -199,8 +414,20 @@ fn format_csv(content: &str) -> Result<String, String> {
     Ok(lines.join("\n"))
 }
 
-/// Pretty-print XML with indentation. Returns an error on parse failure.
-fn format_xml(content: &str) -> Result<String, String> {
+/// Align CSV in the standard dialect (comma, double quotes, header row), as written for table views.
+fn align_csv(csv: &str) -> Result<String, String> {
+    let dialect = CsvDialect {
+        delimiter: ',',
+        quote: '"',
+        backslash_escape: false,
+        has_header: true,
+    };
+    format_csv(csv, &dialect)
+}
+
+/// Pretty-print XML with indentation, or write it without whitespace between tags when `compact`
+/// is set. Returns an error on parse failure.
+fn format_xml(content: &str, options: &FormatOptions) -> Result<String, String> {
     use quick_xml::events::Event;
     use quick_xml::reader::Reader;
     use quick_xml::writer::Writer;
@@ -208,12 +435,38 @@ fn format_xml(content: &str) -> Result<String, String> {
     let mut buf = Vec::new();
     let mut reader = Reader::from_reader(Cursor::new(content.as_bytes()));
     reader.config_mut().trim_text(true);
-    let mut writer = Writer::new_with_indent(Cursor::new(Vec::new()), b' ', 2);
+    let mut writer = if options.compact {
+        Writer::new(Cursor::new(Vec::new()))
+    } else if options.use_tabs {
+        Writer::new_with_indent(Cursor::new(Vec::new()), b'\t', 1)
+    } else {
+        Writer::new_with_indent(Cursor::new(Vec::new()), b' ', options.indent_width(2))
+    };
+    let unit = options.indent_unit(2);
+    let wrap = options.wrap_attributes && !options.compact;
+    let mut depth = 0;
 
     loop {
         let ev = reader.read_event_into(&mut buf).map_err(|e| e.to_string())?;
         match ev {
             Event::Eof => break,
+            Event::Start(e) if wrap => {
+                let e = wrap_attributes(e, &unit, depth, options.max_width);
+                writer.write_event(Event::Start(e)).map_err(|e| e.to_string())?;
+                depth += 1;
+            }
+            Event::Empty(e) if wrap => {
+                let e = wrap_attributes(e, &unit, depth, options.max_width);
+                writer.write_event(Event::Empty(e)).map_err(|e| e.to_string())?;
+            }
+            Event::Start(_) => {
+                writer.write_event(ev).map_err(|e| e.to_string())?;
+                depth += 1;
+            }
+            Event::End(_) => {
+                writer.write_event(ev).map_err(|e| e.to_string())?;
+                depth -= 1;
+            }
             _ => writer.write_event(ev).map_err(|e| e.to_string())?,
         }
         buf.clear();
@@ -223,11 +476,38 @@ fn format_xml(content: &str) -> Result<String, String> {
     String::from_utf8(out).map_err(|e| e.to_string())
 }
 
-/// Pretty-print YAML. Returns an error on parse failure.
-fn format_yaml(content: &str) -> Result<String, String> {
-    let value: serde_yaml::Value =
-        serde_yaml::from_str(content).map_err(|e| e.to_string())?;
-    serde_yaml::to_string(&value).map_err(|e| e.to_string())
+/// Put each attribute of a tag with several attributes on its own line, one level deeper than the
+/// tag at `depth`, unless the tag fits on one line within `max_width` (0 for no limit).
+fn wrap_attributes<'a>(
+    tag: quick_xml::events::BytesStart<'a>,
+    unit: &str,
+    depth: usize,
+    max_width: usize,
+) -> quick_xml::events::BytesStart<'a> {
+    let attributes: Vec<String> = tag
+        .attributes()
+        .filter_map(|a| a.ok())
+        .map(|a| {
+            let key = String::from_utf8_lossy(a.key.as_ref()).into_owned();
+            let value = String::from_utf8_lossy(&a.value).into_owned();
+            if value.contains('"') {
+                format!("{}='{}'", key, value)
+            } else {
+                format!("{}=\"{}\"", key, value)
+            }
+        })
+        .collect();
+    let name = String::from_utf8_lossy(tag.name().as_ref()).into_owned();
+    let width = unit.len() * depth
+        + name.len()
+        + attributes.iter().map(|a| a.len() + 1).sum::<usize>()
+        + 3;
+    if attributes.len() < 2 || (max_width > 0 && width <= max_width) {
+        return tag;
+    }
+    let separator = format!("\n{}", unit.repeat(depth + 1));
+    let content = format!("{}{}{}", name, separator, attributes.join(&separator));
+    quick_xml::events::BytesStart::from_content(content, name.len()).into_owned()
 }
 
 /// Normalize .env / properties: trim lines, sort key=value lines. Comment lines are kept but may reorder.
@@ -241,16 +521,25 @@ fn format_properties(content: &str) -> Result<String, String> {
     Ok(lines.join("\n"))
 }
 
-/// Format content by segment: pretty-print only JSON segments, leave the rest unchanged.
-/// Never fails — returns original content or partially formatted content.
+/// Format content by segment, each with the formatter for its kind (see `format_segment`); content
+/// without segments is formatted as NDJSON or JSON. Output style comes from `options` (defaults when
+/// omitted). Never fails — returns original content or partially formatted content.
 #[tauri::command]
-fn format_content_segmented(content: String, segments: Vec<Segment>) -> String {
+fn format_content_segmented(
+    content: String,
+    segments: Vec<Segment>,
+    options: Option<FormatOptions>,
+) -> String {
+    let options = options.unwrap_or_default();
     let lines: Vec<&str> = content.lines().collect();
     if lines.is_empty() {
         return content;
     }
     if segments.is_empty() {
-        return format_json(content.clone()).unwrap_or(content);
+        if ndjson::is_ndjson(&content) {
+            return options.finish(ndjson::format_ndjson(&content, &options));
+        }
+        return options.finish(render_json(&content, &options).unwrap_or(content));
     }
     let mut out: Vec<String> = Vec::new();
     for seg in segments {
@@ -259,18 +548,69 @@ fn format_content_segmented(content: String, segments: Vec<Segment>) -> String {
         if start >= end {
             continue;
         }
-        let segment_text = lines[start..end].join("\n");
-        let formatted = match seg.kind.as_str() {
-            "json" => format_json(segment_text.clone()).unwrap_or(segment_text),
-            "csv" => format_csv(&segment_text).unwrap_or(segment_text),
-            "xml" | "html" => format_xml(&segment_text).unwrap_or(segment_text),
-            "yaml" => format_yaml(&segment_text).unwrap_or(segment_text),
-            "properties" | "env" => format_properties(&segment_text).unwrap_or(segment_text),
-            _ => segment_text,
-        };
-        out.push(formatted);
+        let text = lines[start..end].join("\n");
+        out.push(format_segment(&seg.kind, text, seg.dialect.as_ref(), &options));
     }
-    out.join("\n")
+    options.finish(out.join("\n"))
+}
+
+/// Format text of the given kind; kinds without a formatter, and text that fails to parse, are returned unchanged.
+/// CSV is read in `dialect` when given, else in the dialect sniffed from the text.
+fn format_segment(
+    kind: &str,
+    text: String,
+    dialect: Option<&CsvDialect>,
+    options: &FormatOptions,
+) -> String {
+    if options.compact {
+        return minify(kind, &text, options).unwrap_or(text);
+    }
+    match kind {
+        "json" => render_json(&text, options).unwrap_or(text),
+        "ndjson" => ndjson::format_ndjson(&text, options),
+        "csv" => {
+            let dialect = dialect.cloned().unwrap_or_else(|| csv_dialect::sniff(&text, ""));
+            format_csv(&text, &dialect).unwrap_or(text)
+        }
+        "xml" => format_xml(&text, options).unwrap_or(text),
+        "html" => html::format_html(&text, &options.indent_unit(2)),
+        "yaml" => yaml::format_yaml(&text, options.indent_width(2)).unwrap_or(text),
+        "properties" | "env" => format_properties(&text).unwrap_or(text),
+        "toml" => toml::format_document(&text, &options.indent_unit(4)).unwrap_or(text),
+        "ini" => ini::format_ini(&text, options.sort_keys),
+        "sql" => sql::format_sql(&text, &options.indent_unit(2)),
+        "http" => http::format_http(&text, options),
+        _ => text,
+    }
+}
+
+/// Minify text of the given kind: JSON (and each NDJSON record) on one line, XML without
+/// insignificant whitespace, YAML in flow style. Fails for other kinds and for text that doesn't parse.
+fn minify(kind: &str, text: &str, options: &FormatOptions) -> Result<String, String> {
+    let options = FormatOptions {
+        compact: true,
+        ..options.clone()
+    };
+    match kind {
+        "json" => render_json(text, &options),
+        "ndjson" => Ok(ndjson::format_ndjson(text, &options)),
+        "xml" => format_xml(text, &options),
+        "yaml" => yaml::flow_yaml(text),
+        _ => Err(format!("{} content has no compact form", kind)),
+    }
+}
+
+/// Minify the whole buffer as `kind`, detected from the content when omitted, e.g. to fit a
+/// payload on one line of a curl command or an environment variable.
+#[tauri::command]
+fn minify_content(
+    content: String,
+    kind: Option<String>,
+    options: Option<FormatOptions>,
+) -> Result<String, String> {
+    let kind = kind.unwrap_or_else(|| content_detection_heuristic(&content).0.to_string());
+    let options = options.unwrap_or_default();
+    Ok(options.finish(minify(&kind, &content, &options)?))
 }
 
 /// Detect content type for a single line (for per-line segment detection).
@@ -280,39 +620,96 @@ fn detect_line_kind(line: &str, line_index: usize, ext: &str) -> String {
         return "text".to_string();
     }
     if line_index == 0 && !ext.is_empty() {
-        let kind = match ext.to_lowercase().as_str() {
-            "json" => "json",
-            "csv" => "csv",
-            "xml" | "html" => "xml",
-            "yaml" | "yml" => "yaml",
-            "env" | "properties" => "properties",
-            _ => content_detection_heuristic(trimmed).0,
-        };
+        let kind = kind_from_extension(ext)
+            .map(|(kind, _)| kind)
+            .unwrap_or_else(|| content_detection_heuristic(trimmed).0);
         return kind.to_string();
     }
     content_detection_heuristic(trimmed).0.to_string()
 }
 
+/// True if a line of `kind` can extend a segment of `segment_kind`: the same kind, or a line
+/// that is only ambiguous on its own (a JSON line in NDJSON, a bare `key=value` or `[section]` in TOML/INI,
+/// a line of text between HTML tags).
+fn continues_segment(segment_kind: &str, kind: &str) -> bool {
+    segment_kind == kind
+        || matches!(
+            (segment_kind, kind),
+            ("ndjson", "json")
+                | ("toml", "properties")
+                | ("ini", "properties")
+                | ("ini", "toml")
+                | ("html", "text")
+        )
+}
+
+/// One segment of `kind` covering every line of the buffer.
+fn whole_buffer(content: &str, kind: &str) -> Vec<Segment> {
+    vec![Segment {
+        start_line: 1,
+        end_line: content.lines().count().max(1) as u32,
+        kind: kind.to_string(),
+        label: None,
+        dialect: None,
+    }]
+}
+
 /// Split content into segments: detect type per line, merge consecutive same kind. Blank lines force a boundary.
+/// Runs of single-line JSON documents become one `ndjson` segment. Markdown (by extension, or text with
+/// fenced code blocks) is split at its fenced code blocks instead, email at its MIME parts, SQL scripts at
+/// statement boundaries, and YAML streams at their `---` document markers (each document labelled by its
+/// `kind`/`metadata.name`). An HTML document is one segment, as is a `.toml`, `.ini` or `.csv`/`.tsv` file
+/// (the latter with its dialect).
+/// An SQL statement elsewhere extends over its continuation lines.
+/// Stack traces (including their blank lines and chained causes) become one `stacktrace` segment each,
+/// HTTP messages (start line, headers and body) one `http` segment each, and PEM blocks (a certificate bundle
+/// together) one `pem` segment.
 #[tauri::command]
 fn detect_segments(content: String, extension: Option<String>) -> Vec<Segment> {
     let ext = extension.as_deref().unwrap_or("");
+    let ext_kind = kind_from_extension(ext).map(|(kind, _)| kind);
+    // A heading and some prose isn't enough: scripts and logs have `# comment` lines too.
+    if ext_kind == Some("markdown") || (ext_kind.is_none() && markdown::has_fenced_block(&content)) {
+        return markdown::markdown_segments(&content);
+    }
+    if ext_kind == Some("email") || (ext_kind.is_none() && email::looks_like_email(&content)) {
+        return email::email_segments(&content);
+    }
+    if ext_kind == Some("sql") || (ext_kind.is_none() && sql::is_sql_script(&content)) {
+        return sql::statement_segments(&content);
+    }
+    if ext_kind == Some("yaml") || (ext_kind.is_none() && yaml::is_yaml_stream(&content)) {
+        return yaml::document_segments(&content);
+    }
+    // Rows are read in one dialect; a line on its own can't tell a header from text.
+    if ext_kind == Some("csv") {
+        let mut segments = whole_buffer(&content, "csv");
+        segments[0].dialect = Some(csv_dialect::sniff(&content, ext));
+        return segments;
+    }
+    // A TOML fragment cut at a blank line may not parse on its own (e.g. inside a multi-line array),
+    // and an INI key belongs to the section above it, wherever the blank lines fall.
+    if let Some(kind @ ("toml" | "ini")) = ext_kind {
+        return whole_buffer(&content, kind);
+    }
+    // Formatting HTML piece by piece would lose `<pre>` whitespace and the nesting depth.
+    if ext_kind == Some("html")
+        || (ext_kind.is_none() && content_detection_heuristic(&content).0 == "html")
+    {
+        return whole_buffer(&content, "html");
+    }
     let lines: Vec<&str> = content.lines().collect();
     if lines.is_empty() {
-        let kind = if matches!(ext.to_lowercase().as_str(), "json" | "csv" | "xml" | "html" | "yaml" | "yml" | "env" | "properties") {
-            match ext.to_lowercase().as_str() {
-                "html" => "xml".to_string(),
-                "yml" => "yaml".to_string(),
-                "env" | "properties" => "properties".to_string(),
-                other => other.to_string(),
-            }
-        } else {
-            content_detection_heuristic(content.trim()).0.to_string()
-        };
+        let kind = kind_from_extension(ext)
+            .map(|(kind, _)| kind)
+            .unwrap_or_else(|| content_detection_heuristic(content.trim()).0)
+            .to_string();
         return vec![Segment {
             start_line: 1,
             end_line: 1,
             kind,
+            label: None,
+            dialect: None,
         }];
     }
     let mut segments: Vec<Segment> = Vec::new();
@@ -325,9 +722,52 @@ fn detect_segments(content: String, extension: Option<String>) -> Vec<Segment> {
             i += 1;
             continue;
         }
+        if let Some(len) = pem::block_len(&lines[i..]) {
+            segments.push(Segment {
+                start_line: line_1based,
+                end_line: (i + len) as u32,
+                kind: "pem".to_string(),
+                label: None,
+                dialect: None,
+            });
+            i += len;
+            continue;
+        }
+        if let Some(len) = http::message_len(&lines[i..]) {
+            segments.push(Segment {
+                start_line: line_1based,
+                end_line: (i + len) as u32,
+                kind: "http".to_string(),
+                label: None,
+                dialect: None,
+            });
+            i += len;
+            continue;
+        }
+        if let Some(len) = stacktrace::trace_len(&lines[i..]) {
+            segments.push(Segment {
+                start_line: line_1based,
+                end_line: (i + len) as u32,
+                kind: "stacktrace".to_string(),
+                label: None,
+                dialect: None,
+            });
+            i += len;
+            continue;
+        }
+        if let Some(last) = segments.last_mut() {
+            if last.kind == "sql"
+                && last.end_line + 1 == line_1based
+                && sql::continues_statement(lines[i - 1], line)
+            {
+                last.end_line = line_1based;
+                i += 1;
+                continue;
+            }
+        }
         let kind = detect_line_kind(line, i, ext);
         if let Some(last) = segments.last_mut() {
-            if last.kind == kind && last.end_line + 1 == line_1based {
+            if continues_segment(&last.kind, &kind) && last.end_line + 1 == line_1based {
                 last.end_line = line_1based;
                 i += 1;
                 continue;
@@ -337,14 +777,32 @@ fn detect_segments(content: String, extension: Option<String>) -> Vec<Segment> {
             start_line: line_1based,
             end_line: line_1based,
             kind,
+            label: None,
+            dialect: None,
         });
         i += 1;
     }
+    // Per-line kinds can be ambiguous; settle multi-line segments by looking at them as a whole.
+    for seg in segments.iter_mut().filter(|s| s.end_line > s.start_line) {
+        // A kind given by the extension stands.
+        if seg.start_line == 1 && ext_kind == Some(seg.kind.as_str()) {
+            continue;
+        }
+        let text = lines[(seg.start_line - 1) as usize..seg.end_line as usize].join("\n");
+        let refined = match seg.kind.as_str() {
+            "json" if ndjson::is_ndjson(&text) => "ndjson",
+            "toml" if !toml::looks_like_toml(&text) && ini::looks_like_ini(&text) => "ini",
+            _ => continue,
+        };
+        seg.kind = refined.to_string();
+    }
     if segments.is_empty() {
         segments.push(Segment {
             start_line: 1,
             end_line: lines.len().max(1) as u32,
             kind: "text".to_string(),
+            label: None,
+            dialect: None,
         });
     }
     segments
@@ -352,17 +810,51 @@ fn detect_segments(content: String, extension: Option<String>) -> Vec<Segment> {
 
 fn content_detection_heuristic(content: &str) -> (&'static str, f64) {
     let trimmed = content.trim();
+    if (trimmed.starts_with('{') || trimmed.starts_with('[')) && ndjson::is_ndjson(trimmed) {
+        return ("ndjson", 0.9);
+    }
+    if pem::looks_like_pem(trimmed) {
+        return ("pem", 0.95);
+    }
+    if toml::looks_like_toml(trimmed) {
+        return ("toml", 0.8);
+    }
+    if ini::looks_like_ini(trimmed) {
+        return ("ini", 0.75);
+    }
+    if markdown::looks_like_markdown(trimmed) {
+        return ("markdown", 0.75);
+    }
+    // `[remote "origin"]` starts like a JSON array of strings.
+    if ini::is_section_header(trimmed) {
+        return ("ini", 0.75);
+    }
+    if trimmed.starts_with('"') && json_string::looks_like_stringified_json(trimmed) {
+        return ("json", 0.85);
+    }
     if trimmed.starts_with('{') && trimmed.contains('"') {
         return ("json", 0.85);
     }
     if trimmed.starts_with('[') && trimmed.contains('"') {
         return ("json", 0.85);
     }
-    if trimmed.contains(',') && trimmed.contains('\n') {
-        let first = trimmed.lines().next().unwrap_or("");
-        if first.matches(',').count() >= 1 {
-            return ("csv", 0.7);
-        }
+    if html::looks_like_html(trimmed) {
+        return ("html", 0.8);
+    }
+    if sql::looks_like_sql(trimmed) {
+        return ("sql", 0.8);
+    }
+    if email::looks_like_email(trimmed) {
+        return ("email", 0.85);
+    }
+    if http::looks_like_http(trimmed) {
+        return ("http", 0.85);
+    }
+    if stacktrace::looks_like_stacktrace(trimmed) {
+        return ("stacktrace", 0.85);
+    }
+    if csv_dialect::looks_like_csv(trimmed) {
+        return ("csv", 0.7);
     }
     // YAML: document start or key: value style
     if trimmed.starts_with("---") {
@@ -390,96 +882,421 @@ fn content_detection_heuristic(content: &str) -> (&'static str, f64) {
     ("text", 0.5)
 }
 
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    tauri::Builder::default()
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .invoke_handler(tauri::generate_handler![read_file, write_file, detect_content, binary_info, detect_segments, compute_diff, compute_diff_structured, format_json, format_content_segmented, minify_content, ndjson::ndjson_records, toml::format_toml, ini::format_ini_sections, ini::ini_duplicate_keys, html::html_text_content, markdown::markdown_outline, stacktrace::stack_traces, http::http_messages, http::http_file_messages, email::email_message, decode::decode_value, json_string::unescape_json, json_string::escape_json_string, json_string::stringified_json, timestamps::find_timestamps, timestamps::convert_timestamps, sqlite::sqlite_schema, sqlite::sqlite_rows, sqlite::sqlite_query, columnar::columnar_metadata, columnar::columnar_rows, columnar::columnar_export, spreadsheet::spreadsheet_sheets, spreadsheet::spreadsheet_export_csv, serialized::decode_serialized, protobuf::decode_protobuf, pem::pem_summary, yaml::yaml_outline])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::Write;
 
     #[test]
     fn detect_content_json_by_extension() {
-        let out = detect_content("anything", Some("json".into()));
+        let out = detect_content("anything", Some("json".into()), None);
         assert_eq!(out.kind, "json");
         assert!((out.confidence - 0.95).abs() < 1e-9);
     }
 
     #[test]
     fn detect_content_csv_by_extension() {
-        let out = detect_content("anything", Some("csv".into()));
+        let out = detect_content("anything", Some("csv".into()), None);
         assert_eq!(out.kind, "csv");
         assert!((out.confidence - 0.95).abs() < 1e-9);
     }
 
     #[test]
     fn detect_content_xml_by_extension() {
-        let out = detect_content("x", Some("xml".into()));
+        let out = detect_content("x", Some("xml".into()), None);
         assert_eq!(out.kind, "xml");
-        let out_html = detect_content("x", Some("HTML".into()));
-        assert_eq!(out_html.kind, "xml");
+        let out_html = detect_content("x", Some("HTML".into()), None);
+        assert_eq!(out_html.kind, "html");
+    }
+
+    #[test]
+    fn detect_content_html_heuristic() {
+        let out = detect_content("<!DOCTYPE html>\n<html><body><br></body></html>", None, None);
+        assert_eq!(out.kind, "html");
+        assert!((out.confidence - 0.8).abs() < 1e-9);
+        assert_eq!(detect_content("<feed><entry/></feed>", None, None).kind, "text");
+    }
+
+    #[test]
+    fn format_content_segmented_formats_html_with_void_elements() {
+        let content = "<div><p>a<br>b</p></div>";
+        let segments = detect_segments(content.to_string(), Some("html".into()));
+        assert_eq!(segments[0].kind, "html");
+        let out = format_content_segmented(content.to_string(), segments, None);
+        assert_eq!(out, "<div>\n  <p>\n    a<br>\n    b\n  </p>\n</div>");
+    }
+
+    #[test]
+    fn format_content_segmented_formats_html_document_as_a_whole() {
+        let content = "<div>\n<pre>\n  a   <b>x</b>\n\n  <i>y</i>   z\n</pre>\n</div>";
+        let segments = detect_segments(content.to_string(), Some("html".into()));
+        assert_eq!(segments.len(), 1);
+        assert_eq!((segments[0].start_line, segments[0].end_line), (1, 7));
+        let out = format_content_segmented(content.to_string(), segments, None);
+        assert!(out.contains("\n  a   <b>x</b>\n\n  <i>y</i>   z\n"), "{}", out);
+        let page = "<body>\n<p>a</p>\n\n<p>b</p>\n</body>";
+        let segments = detect_segments(page.to_string(), None);
+        assert_eq!(segments.len(), 1);
+        assert_eq!(
+            format_content_segmented(page.to_string(), segments, None),
+            "<body>\n  <p>\n    a\n  </p>\n  <p>\n    b\n  </p>\n</body>"
+        );
     }
 
     #[test]
     fn detect_content_json_heuristic_object() {
-        let out = detect_content(r#"  {"a": 1}  "#, None);
+        let out = detect_content(r#"  {"a": 1}  "#, None, None);
         assert_eq!(out.kind, "json");
         assert!((out.confidence - 0.85).abs() < 1e-9);
     }
 
     #[test]
     fn detect_content_json_heuristic_array() {
-        let out = detect_content(r#"["x", "y"]"#, None);
+        let out = detect_content(r#"["x", "y"]"#, None, None);
         assert_eq!(out.kind, "json");
     }
 
     #[test]
     fn detect_content_csv_heuristic() {
-        let out = detect_content("a,b,c\n1,2,3", None);
+        let out = detect_content("a,b,c\n1,2,3", None, None);
         assert_eq!(out.kind, "csv");
         assert!((out.confidence - 0.7).abs() < 1e-9);
     }
 
     #[test]
     fn detect_content_fallback_text() {
-        let out = detect_content("plain text\nno structure", None);
+        let out = detect_content("plain text\nno structure", None, None);
         assert_eq!(out.kind, "text");
         assert!((out.confidence - 0.5).abs() < 1e-9);
     }
 
     #[test]
     fn detect_content_extension_overrides_heuristic() {
-        let out = detect_content("a,b,c\n1,2,3", Some("json".into()));
+        let out = detect_content("a,b,c\n1,2,3", Some("json".into()), None);
         assert_eq!(out.kind, "json");
         assert!((out.confidence - 0.95).abs() < 1e-9);
     }
 
+    #[test]
+    fn detect_content_identifies_binary_file_by_signature() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"GIF89a\x40\x01\xf0\x00\x80\x00\x00").unwrap();
+        let path = file.path().to_string_lossy().into_owned();
+        let out = detect_content("", Some("txt".into()), Some(path));
+        assert_eq!(out.kind, "gif");
+        let info = out.binary.unwrap();
+        assert_eq!((info.width, info.height), (Some(320), Some(240)));
+        // Without a path, the text buffer isn't checked for signatures.
+        assert_eq!(detect_content("GIF89a", None, None).kind, "text");
+    }
+
     #[test]
     fn detect_content_yaml_and_properties_by_extension() {
-        let out = detect_content("x", Some("yaml".into()));
+        let out = detect_content("x", Some("yaml".into()), None);
         assert_eq!(out.kind, "yaml");
-        let out2 = detect_content("x", Some("yml".into()));
+        let out2 = detect_content("x", Some("yml".into()), None);
         assert_eq!(out2.kind, "yaml");
-        let out3 = detect_content("x", Some("env".into()));
+        let out3 = detect_content("x", Some("env".into()), None);
         assert_eq!(out3.kind, "properties");
-        let out4 = detect_content("x", Some("properties".into()));
+        let out4 = detect_content("x", Some("properties".into()), None);
         assert_eq!(out4.kind, "properties");
     }
 
+    #[test]
+    fn detect_and_format_multi_document_yaml() {
+        let content = "kind: ConfigMap\nmetadata: {name: app}\ndata: {a: '1'}\n---\nkind: Secret\nmetadata:\n    name:   token   # rotated weekly\n";
+        let segments = detect_segments(content.to_string(), None);
+        let labels: Vec<(u32, u32, Option<&str>)> = segments
+            .iter()
+            .map(|s| (s.start_line, s.end_line, s.label.as_deref()))
+            .collect();
+        assert_eq!(labels, vec![(1, 3, Some("ConfigMap/app")), (4, 7, Some("Secret/token"))]);
+        let out = format_content_segmented(content.to_string(), segments, None);
+        assert_eq!(
+            out,
+            "kind: ConfigMap\nmetadata: {name: app}\ndata: {a: '1'}\n---\nkind: Secret\nmetadata:\n  name: token # rotated weekly"
+        );
+        assert_eq!(format_content_segmented(content.to_string(), Vec::new(), None), content);
+    }
+
+    #[test]
+    fn detect_content_ndjson_by_extension_and_heuristic() {
+        assert_eq!(detect_content("x", Some("jsonl".into()), None).kind, "ndjson");
+        assert_eq!(detect_content("x", Some("NDJSON".into()), None).kind, "ndjson");
+        let out = detect_content("{\"a\": 1}\n{\"a\": 2}\n", None, None);
+        assert_eq!(out.kind, "ndjson");
+        assert!((out.confidence - 0.9).abs() < 1e-9);
+    }
+
+    #[test]
+    fn detect_segments_merges_json_lines_into_ndjson() {
+        let content = "log start\n{\"a\": 1}\n{\"a\": 2}\n{\"a\": 3}";
+        let out = detect_segments(content.to_string(), None);
+        assert_eq!(out.len(), 2);
+        assert_eq!(out[1].kind, "ndjson");
+        assert_eq!(out[1].start_line, 2);
+        assert_eq!(out[1].end_line, 4);
+        let by_ext = detect_segments("{\"a\": 1}\n{\"a\": 2}".to_string(), Some("jsonl".into()));
+        assert_eq!(by_ext.len(), 1);
+        assert_eq!(by_ext[0].kind, "ndjson");
+    }
+
+    #[test]
+    fn format_content_segmented_formats_ndjson_records() {
+        let content = "{\"a\":1}\n{\"b\":2}";
+        let out = format_content_segmented(content.to_string(), vec![], None);
+        assert_eq!(out, "{\n  \"a\": 1\n}\n{\n  \"b\": 2\n}");
+    }
+
+    #[test]
+    fn detect_content_toml_by_extension_and_heuristic() {
+        assert_eq!(detect_content("x", Some("toml".into()), None).kind, "toml");
+        let cargo = "[package]\nname = \"siftview\"\nauthors = [\"a\", \"b\"]\n\n[dependencies]\nserde = \"1\"";
+        let out = detect_content(cargo, None, None);
+        assert_eq!(out.kind, "toml");
+        assert!((out.confidence - 0.8).abs() < 1e-9);
+        assert_eq!(detect_content("PORT=8080\nHOST=localhost", None, None).kind, "properties");
+    }
+
+    #[test]
+    fn format_content_segmented_does_not_sort_toml() {
+        let content = "[b]\nz=1\n[a]\ny=2";
+        let segments = detect_segments(content.to_string(), Some("toml".into()));
+        assert!(segments.iter().all(|s| s.kind == "toml"));
+        let out = format_content_segmented(content.to_string(), segments, None);
+        assert_eq!(out, "[b]\nz = 1\n\n[a]\ny = 2");
+    }
+
+    #[test]
+    fn format_content_segmented_formats_toml_file_as_a_whole() {
+        let content = "# config\n[server]\nhost = \"x\"\nports = [\n  1,\n  2,\n]\n\n[db]\nname=\"y\"";
+        let segments = detect_segments(content.to_string(), Some("toml".into()));
+        assert_eq!(segments.len(), 1);
+        assert_eq!((segments[0].kind.as_str(), segments[0].end_line), ("toml", 10));
+        let out = format_content_segmented(content.to_string(), segments, None);
+        assert_eq!(
+            out,
+            "# config\n[server]\nhost = \"x\"\nports = [\n    1,\n    2,\n]\n\n[db]\nname = \"y\""
+        );
+    }
+
+    #[test]
+    fn detect_segments_keeps_kind_given_by_extension() {
+        let content = "{\"a\": 1}\n{\"b\": 2}";
+        let segments = detect_segments(content.to_string(), Some("json".into()));
+        assert_eq!(segments[0].kind, "json");
+        assert_eq!(detect_segments(content.to_string(), None)[0].kind, "ndjson");
+    }
+
+    #[test]
+    fn detect_segments_keeps_quoted_ini_sections_together() {
+        let content = "[core]\n\tbare = false\n\n[remote \"origin\"]\n\turl = git@host:repo.git";
+        let segments = detect_segments(content.to_string(), Some("ini".into()));
+        assert_eq!(segments.len(), 1);
+        assert_eq!((segments[0].kind.as_str(), segments[0].end_line), ("ini", 5));
+        // Without the extension the first section is also valid TOML; the quoted one is not JSON.
+        let segments = detect_segments(content.to_string(), None);
+        let last = segments.last().unwrap();
+        assert_eq!((last.kind.as_str(), last.start_line, last.end_line), ("ini", 4, 5));
+        assert_eq!(detect_content("[\"a\", \"b\"]", None, None).kind, "json");
+    }
+
+    #[test]
+    fn yaml_with_mail_like_keys_is_not_email() {
+        let content = "from: alice\nto: bob\nsubject: hi\nbody:\n  text: hello";
+        assert_eq!(detect_content(content, None, None).kind, "yaml");
+        let segments = detect_segments(content.to_string(), None);
+        assert!(segments.iter().all(|s| s.kind != "email"));
+    }
+
+    #[test]
+    fn detect_ini_by_extension_content_and_segments() {
+        assert_eq!(detect_content("x", Some("ini".into()), None).kind, "ini");
+        let content = "[service]\nname=svc\npath=C:\\svc.exe\n[log]\nlevel=debug";
+        let out = detect_content(content, None, None);
+        assert_eq!(out.kind, "ini");
+        let segments = detect_segments(content.to_string(), None);
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].kind, "ini");
+        let formatted = format_content_segmented(content.to_string(), segments, None);
+        assert_eq!(formatted, "[service]\nname = svc\npath = C:\\svc.exe\n\n[log]\nlevel = debug");
+    }
+
+    #[test]
+    fn detect_markdown_and_format_fenced_json() {
+        assert_eq!(detect_content("x", Some("md".into()), None).kind, "markdown");
+        let content = "# Ticket\n\nThe payload we got back:\n\n```json\n{\"ok\":false}\n```\n";
+        assert_eq!(detect_content(content, None, None).kind, "markdown");
+        let segments = detect_segments(content.to_string(), None);
+        assert_eq!(segments.len(), 3);
+        assert_eq!(segments[1].kind, "json");
+        let out = format_content_segmented(content.to_string(), segments, None);
+        assert_eq!(
+            out,
+            "# Ticket\n\nThe payload we got back:\n\n```json\n{\n  \"ok\": false\n}\n```"
+        );
+    }
+
+    #[test]
+    fn detect_segments_keeps_commented_script_lines_apart() {
+        let content = "# deploy script for the api\necho starting the deploy now\n{\"a\":1}\nselect * from users;\n";
+        let kinds: Vec<String> = detect_segments(content.to_string(), None)
+            .into_iter()
+            .map(|s| s.kind)
+            .collect();
+        assert!(kinds.contains(&"json".to_string()));
+        assert!(kinds.contains(&"sql".to_string()));
+        assert!(!kinds.contains(&"markdown".to_string()));
+    }
+
+    #[test]
+    fn detect_sql_and_format_statement_segments() {
+        assert_eq!(detect_content("select * from t", None, None).kind, "sql");
+        let content = "-- users\nselect id, name from users where active = 1;\n\ndelete from t where id = 2;";
+        let segments = detect_segments(content.to_string(), None);
+        assert_eq!(segments.len(), 2);
+        assert!(segments.iter().all(|s| s.kind == "sql"));
+        assert_eq!((segments[0].start_line, segments[0].end_line), (1, 2));
+        let out = format_content_segmented(content.to_string(), segments, None);
+        assert_eq!(
+            out,
+            "-- users\nSELECT\n  id,\n  name\nFROM users\nWHERE active = 1;\nDELETE FROM t\nWHERE id = 2;"
+        );
+    }
+
+    #[test]
+    fn format_content_segmented_keeps_sql_comments_outside_statements() {
+        let sql = Some("sql".to_string());
+        let content = "select 1;\n-- done";
+        let segments = detect_segments(content.to_string(), sql.clone());
+        assert_eq!(format_content_segmented(content.to_string(), segments, None), "SELECT 1;\n\n-- done");
+        let content = "-- nothing here yet";
+        let segments = detect_segments(content.to_string(), sql);
+        assert_eq!(format_content_segmented(content.to_string(), segments, None), content);
+    }
+
+    #[test]
+    fn detect_segments_extends_sql_over_continuation_lines() {
+        let content = "2024-01-01 slow query:\nSELECT a\nFROM t\n  WHERE b = 1\n{\"rows\": 3}";
+        let out = detect_segments(content.to_string(), None);
+        let kinds: Vec<(&str, u32, u32)> = out
+            .iter()
+            .map(|s| (s.kind.as_str(), s.start_line, s.end_line))
+            .collect();
+        assert_eq!(kinds, vec![("text", 1, 1), ("sql", 2, 4), ("json", 5, 5)]);
+    }
+
+    #[test]
+    fn detect_segments_finds_stacktrace_in_log() {
+        let content = "12:00:01 INFO started\n12:00:02 ERROR request failed\njava.lang.NullPointerException: id\n\tat com.acme.Api.get(Api.java:12)\n\tat com.acme.Main.main(Main.java:3)\n12:00:03 INFO retrying";
+        let out = detect_segments(content.to_string(), None);
+        let kinds: Vec<(&str, u32, u32)> = out
+            .iter()
+            .map(|s| (s.kind.as_str(), s.start_line, s.end_line))
+            .collect();
+        assert_eq!(kinds, vec![("text", 1, 2), ("stacktrace", 3, 5), ("text", 6, 6)]);
+        let trace = "Traceback (most recent call last):\n  File \"x.py\", line 1, in <module>\nKeyError: 'a'";
+        assert_eq!(detect_content(trace, None, None).kind, "stacktrace");
+    }
+
+    #[test]
+    fn detect_segments_finds_pem_bundle() {
+        let chain = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../test-files/sample-chain.pem"
+        ))
+        .unwrap();
+        let content = format!("Server chain:\n{}\ndone", chain.trim_end().replace("-----\n-----", "-----\n\n-----"));
+        let out = detect_segments(content, None);
+        let kinds: Vec<(&str, u32, u32)> = out
+            .iter()
+            .map(|s| (s.kind.as_str(), s.start_line, s.end_line))
+            .collect();
+        let end = chain.lines().count() as u32 + 3;
+        assert_eq!(kinds, vec![("text", 1, 1), ("pem", 2, end), ("text", end + 1, end + 1)]);
+        assert_eq!(detect_content(&chain, None, None).kind, "pem");
+        assert_eq!(detect_content("", Some("crt".to_string()), None).kind, "pem");
+    }
+
+    #[test]
+    fn detect_and_format_http_message_body_as_json() {
+        let content = "HTTP/1.1 200 OK\nContent-Type: application/json\nCache-Control: no-cache\n\n{\"ok\":true}";
+        assert_eq!(detect_content(content, None, None).kind, "http");
+        let segments = detect_segments(content.to_string(), None);
+        assert_eq!(segments.len(), 1);
+        assert_eq!((segments[0].kind.as_str(), segments[0].end_line), ("http", 5));
+        let out = format_content_segmented(content.to_string(), segments, None);
+        assert_eq!(
+            out,
+            "HTTP/1.1 200 OK\nContent-Type: application/json\nCache-Control: no-cache\n\n{\n  \"ok\": true\n}"
+        );
+    }
+
+    #[test]
+    fn detect_email_and_segment_plain_text_parts() {
+        let content = "From: a@example.com\nTo: b@example.com\nSubject: hook\nContent-Type: multipart/mixed; boundary=x\n\n--x\nContent-Type: application/json\n\n{\"id\":1}\n--x--";
+        assert_eq!(detect_content(content, None, None).kind, "email");
+        assert_eq!(detect_content("", Some("eml".into()), None).kind, "email");
+        let segments = detect_segments(content.to_string(), None);
+        let kinds: Vec<(&str, u32, u32)> = segments
+            .iter()
+            .map(|s| (s.kind.as_str(), s.start_line, s.end_line))
+            .collect();
+        assert_eq!(kinds, vec![("email", 1, 8), ("json", 9, 9), ("email", 10, 10)]);
+        let out = format_content_segmented(content.to_string(), segments, None);
+        assert!(out.contains("{\n  \"id\": 1\n}\n--x--"));
+    }
+
     #[test]
     fn format_csv_aligns_columns() {
         let raw = "a,b,c\n1,22,333";
-        let out = format_csv(raw).unwrap();
+        let out = format_csv(raw, &csv_dialect::sniff(raw, "")).unwrap();
         assert!(out.contains("a  "));
         assert!(out.contains("1  "));
         assert!(out.contains("333"));
     }
 
+    #[test]
+    fn format_csv_uses_sniffed_dialect() {
+        let raw = "name;note\nfoo;\"a;b\"\nlonger;c";
+        let out = format_csv(raw, &csv_dialect::sniff(raw, "")).unwrap();
+        assert_eq!(out, "name    note\nfoo     a;b \nlonger  c   ");
+    }
+
+    #[test]
+    fn detect_content_reports_csv_dialect() {
+        let out = detect_content("id\tname\n1\tx", Some("tsv".into()), None);
+        assert_eq!(out.kind, "csv");
+        let dialect = out.dialect.unwrap();
+        assert_eq!(dialect.delimiter, '\t');
+        assert!(dialect.has_header);
+        let out = detect_content("a;b;c\n1;2;3", None, None);
+        assert_eq!(out.kind, "csv");
+        assert_eq!(out.dialect.unwrap().delimiter, ';');
+        assert!(detect_content("{\"a\": 1}", None, None).dialect.is_none());
+    }
+
     #[test]
     fn format_content_segmented_formats_csv_segment() {
         let content = "text\nname,age\nAlice,30\nmore";
         let segments = vec![
-            Segment { start_line: 1, end_line: 1, kind: "text".to_string() },
-            Segment { start_line: 2, end_line: 3, kind: "csv".to_string() },
-            Segment { start_line: 4, end_line: 4, kind: "text".to_string() },
+            Segment { start_line: 1, end_line: 1, kind: "text".to_string(), label: None, dialect: None },
+            Segment { start_line: 2, end_line: 3, kind: "csv".to_string(), label: None, dialect: None },
+            Segment { start_line: 4, end_line: 4, kind: "text".to_string(), label: None, dialect: None },
         ];
-        let out = format_content_segmented(content.to_string(), segments);
+        let out = format_content_segmented(content.to_string(), segments, None);
         assert!(out.contains("text"));
         assert!(out.contains("more"));
         // CSV segment is aligned (extra spaces between columns)
@@ -487,6 +1304,31 @@ mod tests {
         assert!(out.contains("Alice"));
     }
 
+    #[test]
+    fn detect_segments_reads_csv_by_extension_as_one_segment() {
+        let content = "a\tb\n1\t2\n333\t4\n";
+        let segments = detect_segments(content.to_string(), Some("tsv".into()));
+        assert_eq!(segments.len(), 1);
+        assert_eq!((segments[0].kind.as_str(), segments[0].end_line), ("csv", 3));
+        assert_eq!(segments[0].dialect.as_ref().map(|d| d.delimiter), Some('\t'));
+        let out = format_content_segmented(content.to_string(), segments, None);
+        assert_eq!(out, "a    b\n1    2\n333  4");
+    }
+
+    #[tokio::test]
+    async fn binary_info_reads_whole_file_metadata() {
+        let xlsx = concat!(env!("CARGO_MANIFEST_DIR"), "/../test-files/sample.xlsx");
+        let info = binary_info(xlsx.to_string()).await.unwrap().unwrap();
+        assert_eq!(info.kind, "xlsx");
+        assert!(info.entries.unwrap().iter().any(|e| e == "xl/workbook.xml"));
+        let db = concat!(env!("CARGO_MANIFEST_DIR"), "/../test-files/sample.sqlite");
+        let info = binary_info(db.to_string()).await.unwrap().unwrap();
+        assert_eq!(info.kind, "sqlite");
+        assert!(!info.tables.unwrap().is_empty());
+        let text = concat!(env!("CARGO_MANIFEST_DIR"), "/../test-files/sample.txt");
+        assert!(binary_info(text.to_string()).await.unwrap().is_none());
+    }
+
     #[tokio::test]
     async fn read_file_returns_error_for_nonexistent() {
         let result = read_file("/nonexistent/path/xyz".into()).await;
@@ -528,17 +1370,113 @@ mod tests {
     #[test]
     fn format_json_pretty_prints() {
         let compact = r#"{"a":1,"b":2}"#;
-        let out = format_json(compact.to_string()).unwrap();
+        let out = format_json(compact.to_string(), None).unwrap();
         assert!(out.contains(' '));
         assert!(out.contains('\n'));
         let parsed: serde_json::Value = serde_json::from_str(&out).unwrap();
         assert_eq!(parsed.get("a").and_then(|v| v.as_i64()), Some(1));
     }
 
+    #[test]
+    fn format_json_keeps_key_order_and_numbers_unless_sorting() {
+        let content = r#"{"id":12345678901234567890,"price":0.10,"name":"a"}"#;
+        assert_eq!(
+            format_json(content.to_string(), None).unwrap(),
+            "{\n  \"id\": 12345678901234567890,\n  \"price\": 0.10,\n  \"name\": \"a\"\n}"
+        );
+        assert_eq!(
+            format_json(
+                content.to_string(),
+                Some(FormatOptions {
+                    sort_keys: true,
+                    ..Default::default()
+                })
+            )
+            .unwrap(),
+            "{\n  \"id\": 12345678901234567890,\n  \"name\": \"a\",\n  \"price\": 0.10\n}"
+        );
+    }
+
+    #[test]
+    fn format_content_segmented_applies_format_options() {
+        let content = "<svc host=\"a\" port=\"80\"><opt/><tls enabled=\"true\"/></svc>\n{\"ports\":[80,443],\"name\":\"web\"}";
+        let segments = vec![
+            Segment { start_line: 1, end_line: 1, kind: "xml".to_string(), label: None, dialect: None },
+            Segment { start_line: 2, end_line: 2, kind: "json".to_string(), label: None, dialect: None },
+        ];
+        let options: FormatOptions = serde_json::from_str(
+            r#"{"use_tabs": true, "max_width": 20, "wrap_attributes": true, "trailing_newline": true}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            format_content_segmented(content.to_string(), segments, Some(options)),
+            "<svc\n\thost=\"a\"\n\tport=\"80\">\n\t<opt/>\n\t<tls enabled=\"true\"/>\n</svc>\n{\n\t\"ports\": [80, 443],\n\t\"name\": \"web\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn format_content_segmented_minifies_when_compact() {
+        let content = "<a>\n  <b x=\"1\"> text </b>\n</a>\nplain  text\n{\n  \"id\": 1,\n  \"tags\": [ \"x\" ]\n}";
+        let segments = vec![
+            Segment { start_line: 1, end_line: 3, kind: "xml".to_string(), label: None, dialect: None },
+            Segment { start_line: 4, end_line: 4, kind: "text".to_string(), label: None, dialect: None },
+            Segment { start_line: 5, end_line: 8, kind: "json".to_string(), label: None, dialect: None },
+        ];
+        let options = FormatOptions { compact: true, ..Default::default() };
+        assert_eq!(
+            format_content_segmented(content.to_string(), segments, Some(options)),
+            "<a><b x=\"1\">text</b></a>\nplain  text\n{\"id\":1,\"tags\":[\"x\"]}"
+        );
+    }
+
+    #[test]
+    fn format_content_segmented_indents_every_kind_with_options() {
+        let content = "select * from (select id from t) u\n<ul><li>a</li></ul>\nlist=[\n1,\n]\na:\n  b: 1";
+        let segments = vec![
+            Segment { start_line: 1, end_line: 1, kind: "sql".to_string(), label: None, dialect: None },
+            Segment { start_line: 2, end_line: 2, kind: "html".to_string(), label: None, dialect: None },
+            Segment { start_line: 3, end_line: 5, kind: "toml".to_string(), label: None, dialect: None },
+            Segment { start_line: 6, end_line: 7, kind: "yaml".to_string(), label: None, dialect: None },
+        ];
+        let options = FormatOptions { use_tabs: true, ..Default::default() };
+        let out = format_content_segmented(content.to_string(), segments.clone(), Some(options));
+        assert!(out.contains("(\n\tSELECT id\n\tFROM t\n) u"), "{}", out);
+        assert!(out.contains("<ul>\n\t<li>\n\t\ta\n\t</li>\n</ul>"), "{}", out);
+        assert!(out.contains("list = [\n\t1,\n]"), "{}", out);
+        let options = FormatOptions { indent: Some(4), ..Default::default() };
+        let out = format_content_segmented(content.to_string(), segments, Some(options));
+        assert!(out.ends_with("a:\n    b: 1"), "{}", out);
+    }
+
+    #[test]
+    fn minify_content_detects_kind_and_rejects_other_content() {
+        let json = "{\n  \"b\": 1,\n  \"a\": [1, 2]\n}";
+        assert_eq!(minify_content(json.to_string(), None, None).unwrap(), r#"{"b":1,"a":[1,2]}"#);
+        let yaml = "name: web\nports:\n  - 80\n  - 443\n";
+        assert_eq!(
+            minify_content(yaml.to_string(), Some("yaml".to_string()), None).unwrap(),
+            "{name: web, ports: [80, 443]}"
+        );
+        assert!(minify_content("just some words".to_string(), None, None).is_err());
+    }
+
     #[test]
     fn format_json_rejects_invalid() {
         let bad = "{ invalid }";
-        assert!(format_json(bad.to_string()).is_err());
+        assert!(format_json(bad.to_string(), None).is_err());
+    }
+
+    #[test]
+    fn detect_and_format_stringified_json() {
+        let content = "\"{\\\"user\\\":{\\\"id\\\":7}}\"";
+        assert_eq!(content_detection_heuristic(content).0, "json");
+        let expected = "{\n  \"user\": {\n    \"id\": 7\n  }\n}";
+        assert_eq!(format_json(content.to_string(), None).unwrap(), expected);
+        let log = format!("log line\n{}", content);
+        let segments = detect_segments(log.clone(), None);
+        assert_eq!(segments[1].kind, "json");
+        let out = format_content_segmented(log, segments, None);
+        assert_eq!(out, format!("log line\n{}", expected));
     }
 
     #[test]
@@ -548,8 +1486,10 @@ mod tests {
             start_line: 1,
             end_line: 2,
             kind: "text".to_string(),
+            label: None,
+            dialect: None,
         }];
-        let out = format_content_segmented(content.to_string(), segments);
+        let out = format_content_segmented(content.to_string(), segments, None);
         assert_eq!(out, content);
     }
 
@@ -557,11 +1497,11 @@ mod tests {
     fn format_content_segmented_formats_only_json_segment() {
         let content = "some text\n{\"a\":1}\nmore text";
         let segments = vec![
-            Segment { start_line: 1, end_line: 1, kind: "text".to_string() },
-            Segment { start_line: 2, end_line: 2, kind: "json".to_string() },
-            Segment { start_line: 3, end_line: 3, kind: "text".to_string() },
+            Segment { start_line: 1, end_line: 1, kind: "text".to_string(), label: None, dialect: None },
+            Segment { start_line: 2, end_line: 2, kind: "json".to_string(), label: None, dialect: None },
+            Segment { start_line: 3, end_line: 3, kind: "text".to_string(), label: None, dialect: None },
         ];
-        let out = format_content_segmented(content.to_string(), segments);
+        let out = format_content_segmented(content.to_string(), segments, None);
         assert!(out.contains("some text"));
         assert!(out.contains("more text"));
         assert!(out.contains("\"a\": 1") || out.contains("\"a\":1"));
@@ -570,7 +1510,7 @@ mod tests {
     #[test]
     fn format_content_segmented_plain_text_no_segments_fallback() {
         let content = "not json at all";
-        let out = format_content_segmented(content.to_string(), vec![]);
+        let out = format_content_segmented(content.to_string(), vec![], None);
         assert_eq!(out, content);
     }
 
@@ -610,15 +1550,3 @@ mod tests {
         assert_eq!(read_back, "written content");
     }
 }
-
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    tauri::Builder::default()
-        .plugin(tauri_plugin_clipboard_manager::init())
-        .plugin(tauri_plugin_dialog::init())
-        .plugin(tauri_plugin_opener::init())
-        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
-        .invoke_handler(tauri::generate_handler![read_file, write_file, detect_content, detect_segments, compute_diff, compute_diff_structured, format_json, format_content_segmented])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
-}
@@ -0,0 +1,243 @@
+//! Markdown: detection, heading outline and fenced-code segmentation.
+
+use crate::{content_detection_heuristic, kind_from_extension, OutlineItem, Segment};
+
+/// An opening or closing code fence: the fence character, its length and the info string.
+fn fence(line: &str) -> Option<(char, usize, &str)> {
+    let t = line.trim_start();
+    if line.len() - t.len() > 3 {
+        return None;
+    }
+    let c = t.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = t.chars().take_while(|x| *x == c).count();
+    (len >= 3).then(|| (c, len, t[len..].trim()))
+}
+
+/// Level and title of an ATX heading (`## Title ##`).
+fn atx_heading(line: &str) -> Option<(u8, &str)> {
+    let t = line.trim_start();
+    let level = t.chars().take_while(|c| *c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &t[level..];
+    if !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        return None;
+    }
+    let title = rest.trim().trim_end_matches('#').trim_end();
+    Some((level as u8, title))
+}
+
+/// Level of a setext underline (`===` is 1, `---` is 2).
+fn setext_level(line: &str) -> Option<u8> {
+    let t = line.trim();
+    if t.len() >= 2 && t.chars().all(|c| c == '=') {
+        Some(1)
+    } else if t.len() >= 2 && t.chars().all(|c| c == '-') {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+/// A line of running text: several words, and not a `key: value`, `key=value` or list item.
+fn is_prose(t: &str) -> bool {
+    let key_value = t
+        .split_once(": ")
+        .is_some_and(|(key, _)| !key.contains(' '));
+    t.split_whitespace().count() >= 3
+        && !t.contains('=')
+        && !t.ends_with(':')
+        && !key_value
+        && !t.starts_with(['-', '#'])
+}
+
+/// True for a fenced code block (opening and closing fence), or a heading alongside running text.
+pub fn looks_like_markdown(content: &str) -> bool {
+    let mut fences = 0;
+    let mut heading = false;
+    let mut prose = false;
+    for line in content.lines() {
+        if fence(line).is_some() {
+            fences += 1;
+        } else if atx_heading(line).is_some() {
+            heading = true;
+        } else if is_prose(line.trim()) {
+            prose = true;
+        }
+    }
+    fences >= 2 || (heading && prose)
+}
+
+/// True if the text holds a fenced code block: an opening and a closing fence.
+pub fn has_fenced_block(content: &str) -> bool {
+    content.lines().filter(|line| fence(line).is_some()).count() >= 2
+}
+
+/// Kind of a fenced block from its info string (` ```json `), or from its content when there is none.
+fn fenced_kind(info: &str, code: &str) -> String {
+    let lang = info.split_whitespace().next().unwrap_or("");
+    if lang.is_empty() {
+        return content_detection_heuristic(code).0.to_string();
+    }
+    kind_from_extension(lang)
+        .map(|(kind, _)| kind)
+        .unwrap_or("text")
+        .to_string()
+}
+
+/// Segments for a Markdown buffer: prose (including the fence lines) as `markdown`, and the
+/// inside of each fenced code block as a segment of the declared kind. Covers every line.
+pub fn markdown_segments(content: &str) -> Vec<Segment> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut segments: Vec<Segment> = Vec::new();
+    let mut push = |start: usize, end: usize, kind: String| {
+        if start < end {
+            segments.push(Segment {
+                start_line: start as u32 + 1,
+                end_line: end as u32,
+                kind,
+                label: None,
+                dialect: None,
+            });
+        }
+    };
+    let mut prose_start = 0;
+    let mut i = 0;
+    while i < lines.len() {
+        let Some((c, len, info)) = fence(lines[i]) else {
+            i += 1;
+            continue;
+        };
+        let code_start = i + 1;
+        let code_end = (code_start..lines.len())
+            .find(|&j| {
+                fence(lines[j])
+                    .is_some_and(|(c2, len2, rest)| c2 == c && len2 >= len && rest.is_empty())
+            })
+            .unwrap_or(lines.len());
+        if code_start < code_end {
+            push(prose_start, code_start, "markdown".to_string());
+            let code = lines[code_start..code_end].join("\n");
+            push(code_start, code_end, fenced_kind(info, &code));
+            prose_start = code_end;
+        }
+        i = code_end + 1;
+    }
+    push(prose_start, lines.len(), "markdown".to_string());
+    if segments.is_empty() {
+        segments.push(Segment {
+            start_line: 1,
+            end_line: 1,
+            kind: "markdown".to_string(),
+            label: None,
+            dialect: None,
+        });
+    }
+    segments
+}
+
+/// Headings of a Markdown document (ATX and setext, outside code blocks). Each heading's range
+/// runs until the next heading of the same or a higher level.
+#[tauri::command]
+pub fn markdown_outline(content: String) -> Vec<OutlineItem> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut items: Vec<OutlineItem> = Vec::new();
+    let mut open_fence: Option<(char, usize)> = None;
+    for (i, line) in lines.iter().enumerate() {
+        if let Some((c, len, info)) = fence(line) {
+            match open_fence {
+                Some((oc, olen)) if c == oc && len >= olen && info.is_empty() => open_fence = None,
+                None => open_fence = Some((c, len)),
+                _ => {}
+            }
+            continue;
+        }
+        if open_fence.is_some() {
+            continue;
+        }
+        let heading = match atx_heading(line) {
+            Some((level, title)) => Some((level, title.to_string(), i)),
+            None => setext_level(line)
+                .filter(|_| i > 0 && !lines[i - 1].trim().is_empty())
+                .filter(|_| atx_heading(lines[i - 1]).is_none() && fence(lines[i - 1]).is_none())
+                .map(|level| (level, lines[i - 1].trim().to_string(), i - 1)),
+        };
+        if let Some((level, title, start)) = heading {
+            items.push(OutlineItem {
+                level,
+                title,
+                start_line: start as u32 + 1,
+                end_line: lines.len() as u32,
+            });
+        }
+    }
+    for i in 0..items.len() {
+        if let Some(next) = items[i + 1..].iter().find(|n| n.level <= items[i].level) {
+            items[i].end_line = next.start_line - 1;
+        }
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = "# Design\n\nSome text about the design.\n\n## Payload\n\n```json\n{\"a\":1}\n```\n\nSetext title\n------------\n\n```\nplain words here\n```\n\n# Appendix\n";
+
+    #[test]
+    fn looks_like_markdown_needs_headings_with_text_or_fences() {
+        assert!(looks_like_markdown(DOC));
+        assert!(looks_like_markdown("see:\n```\nx\n```"));
+        assert!(!looks_like_markdown("# comment\nkey=value\nother=1"));
+        assert!(!looks_like_markdown(
+            "# config\nname: app\nitems:\n  - a b c d"
+        ));
+    }
+
+    #[test]
+    fn markdown_segments_split_out_fenced_code() {
+        let segs = markdown_segments(DOC);
+        let summary: Vec<(u32, u32, &str)> = segs
+            .iter()
+            .map(|s| (s.start_line, s.end_line, s.kind.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, 7, "markdown"),
+                (8, 8, "json"),
+                (9, 14, "markdown"),
+                (15, 15, "text"),
+                (16, 18, "markdown"),
+            ]
+        );
+    }
+
+    #[test]
+    fn markdown_outline_lists_headings_with_ranges() {
+        let outline = markdown_outline(DOC.to_string());
+        let summary: Vec<(u8, &str, u32, u32)> = outline
+            .iter()
+            .map(|o| (o.level, o.title.as_str(), o.start_line, o.end_line))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, "Design", 1, 17),
+                (2, "Payload", 5, 10),
+                (2, "Setext title", 11, 17),
+                (1, "Appendix", 18, 18),
+            ]
+        );
+    }
+
+    #[test]
+    fn markdown_outline_ignores_hashes_in_code() {
+        let outline = markdown_outline("```sh\n# not a heading\n```\n## Real ##".to_string());
+        assert_eq!(outline.len(), 1);
+        assert_eq!(outline[0].title, "Real");
+        assert_eq!(outline[0].start_line, 4);
+    }
+}
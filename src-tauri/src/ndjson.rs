@@ -0,0 +1,122 @@
+//! JSON Lines / NDJSON: one JSON document per line.
+
+use serde::Serialize;
+
+use crate::{json_format, print_json, FormatOptions, ParseError};
+
+/// One record of an NDJSON buffer, for record navigation (1-based line).
+#[derive(Debug, Serialize)]
+pub struct NdjsonRecord {
+    pub index: u32,
+    pub line: u32,
+    /// Set when the record is not valid JSON.
+    pub error: Option<ParseError>,
+}
+
+/// Parse one record; errors are reported against the record's line in the buffer.
+fn parse_record(text: &str, line: u32) -> Result<serde_json::Value, ParseError> {
+    serde_json::from_str(text).map_err(|e| ParseError::from_json(&e, line - 1))
+}
+
+/// True if there are at least two non-blank lines and every one is a standalone JSON object or array.
+pub fn is_ndjson(content: &str) -> bool {
+    let mut records = 0;
+    for line in content.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if !(line.starts_with('{') || line.starts_with('[')) {
+            return false;
+        }
+        if serde_json::from_str::<serde_json::Value>(line).is_err() {
+            return false;
+        }
+        records += 1;
+    }
+    records >= 2
+}
+
+/// Format each record on its own in the style set by `options`. Invalid records and blank lines are
+/// left as-is.
+pub fn format_ndjson(content: &str, options: &FormatOptions) -> String {
+    content
+        .lines()
+        .map(|line| {
+            if line.trim().is_empty() {
+                return line.to_string();
+            }
+            json_format::parse(line)
+                .map(|json| print_json(json, options))
+                .unwrap_or_else(|_| line.to_string())
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// List the records of an NDJSON buffer with their line numbers and any per-record parse error.
+#[tauri::command]
+pub fn ndjson_records(content: String) -> Vec<NdjsonRecord> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .enumerate()
+        .map(|(index, (i, line))| {
+            let line_1based = (i + 1) as u32;
+            NdjsonRecord {
+                index: index as u32,
+                line: line_1based,
+                error: parse_record(line, line_1based).err(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_ndjson_requires_multiple_json_lines() {
+        assert!(is_ndjson("{\"a\":1}\n{\"a\":2}\n"));
+        assert!(is_ndjson("[1,2]\n\n[3]"));
+        assert!(!is_ndjson("{\"a\":1}"));
+        assert!(!is_ndjson("{\n  \"a\": 1\n}"));
+        assert!(!is_ndjson("{\"a\":1}\nplain text"));
+    }
+
+    #[test]
+    fn format_ndjson_formats_each_record() {
+        let out = format_ndjson(
+            "{\"a\":1}\nnot json\n{\"b\":[1,2]}",
+            &FormatOptions::default(),
+        );
+        assert_eq!(
+            out,
+            "{\n  \"a\": 1\n}\nnot json\n{\n  \"b\": [\n    1,\n    2\n  ]\n}"
+        );
+        assert_eq!(
+            format_ndjson("{\"b\":1,\"a\":1.50}", &FormatOptions::default()),
+            "{\n  \"b\": 1,\n  \"a\": 1.50\n}"
+        );
+        let options = FormatOptions {
+            sort_keys: true,
+            compact: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_ndjson("{\"b\": 1, \"a\": [1, 2]}\n[ 3 ]", &options),
+            "{\"a\":[1,2],\"b\":1}\n[3]"
+        );
+    }
+
+    #[test]
+    fn ndjson_records_reports_line_numbers_and_errors() {
+        let records = ndjson_records("{\"a\":1}\n\n{\"a\":,}\n{\"a\":3}".to_string());
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[1].index, 1);
+        assert_eq!(records[1].line, 3);
+        let err = records[1].error.as_ref().unwrap();
+        assert_eq!(err.line, 3);
+        assert_eq!(err.column, 6);
+        assert!(records[0].error.is_none());
+        assert_eq!(records[2].line, 4);
+    }
+}
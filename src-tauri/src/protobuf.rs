@@ -0,0 +1,981 @@
+//! Protobuf wire-format decoder: raw bytes (a file, or a hex/base64 selection, optionally gRPC
+//! framed) decoded to a field-number tree, with types guessed or taken from a `.proto` file or a
+//! descriptor set.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::decode::{as_text, base64, decompress, hex};
+
+/// Deepest nesting of messages and groups decoded.
+const MAX_DEPTH: usize = 32;
+
+const MAX_FIELD_NUMBER: u64 = (1 << 29) - 1;
+
+/// Scalar types of the `.proto` language.
+const SCALARS: &[&str] = &[
+    "double", "float", "int64", "uint64", "int32", "fixed64", "fixed32", "bool", "string", "bytes",
+    "uint32", "sfixed32", "sfixed64", "sint32", "sint64",
+];
+
+/// `FieldDescriptorProto.Type` values, by number.
+const DESCRIPTOR_TYPES: &[&str] = &[
+    "", "double", "float", "int64", "uint64", "int32", "fixed64", "fixed32", "bool", "string",
+    "group", "message", "bytes", "uint32", "enum", "sfixed32", "sfixed64", "sint32", "sint64",
+];
+
+/// A decoded field.
+#[derive(Debug, Serialize)]
+pub struct ProtoField {
+    pub number: u64,
+    /// Field name, when the schema declares the field.
+    pub name: Option<String>,
+    /// `varint`, `i64`, `len`, `group` or `i32`.
+    pub wire_type: &'static str,
+    /// Declared type (`int32`, `string`, a message or enum name, `packed sint32`, ...), or without
+    /// a schema a guess: `varint`, `fixed64`/`double`, `fixed32`/`float`, `string`, `bytes`,
+    /// `message` or `group`.
+    pub kind: String,
+    /// Scalar value: bytes as hex, enums by name, packed fields as an array. Null for messages.
+    pub value: Value,
+    /// Fields of a nested message or group.
+    pub fields: Vec<ProtoField>,
+    /// Byte offset of the field's tag within its message.
+    pub offset: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProtobufDecoding {
+    /// Schema message type used for the top-level messages.
+    pub message_type: Option<String>,
+    /// Whether the payload was gRPC length-prefixed; each frame is then a message.
+    pub grpc: bool,
+    pub messages: Vec<Vec<ProtoField>>,
+    /// The messages in protobuf text format (`1: 150`, `2 { ... }`), separated by blank lines.
+    pub text: String,
+}
+
+enum Raw<'a> {
+    Varint(u64),
+    I64(u64),
+    Len(&'a [u8]),
+    Group(Vec<RawField<'a>>),
+    I32(u32),
+}
+
+struct RawField<'a> {
+    number: u64,
+    offset: usize,
+    value: Raw<'a>,
+}
+
+fn varint(b: &[u8], at: &mut usize) -> Option<u64> {
+    let mut value = 0u64;
+    for i in 0..10 {
+        let byte = *b.get(*at)?;
+        *at += 1;
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn fields_until<'a>(
+    b: &'a [u8],
+    at: &mut usize,
+    group: Option<u64>,
+    depth: usize,
+) -> Option<Vec<RawField<'a>>> {
+    let mut fields = Vec::new();
+    while *at < b.len() {
+        let offset = *at;
+        let tag = varint(b, at)?;
+        let number = tag >> 3;
+        if number == 0 || number > MAX_FIELD_NUMBER {
+            return None;
+        }
+        let value = match tag & 7 {
+            0 => Raw::Varint(varint(b, at)?),
+            1 => {
+                let bytes = b.get(*at..*at + 8)?;
+                *at += 8;
+                Raw::I64(u64::from_le_bytes(bytes.try_into().ok()?))
+            }
+            2 => {
+                let len = usize::try_from(varint(b, at)?).ok()?;
+                let end = at.checked_add(len)?;
+                let bytes = b.get(*at..end)?;
+                *at = end;
+                Raw::Len(bytes)
+            }
+            3 if depth < MAX_DEPTH => Raw::Group(fields_until(b, at, Some(number), depth + 1)?),
+            4 => return (group == Some(number)).then_some(fields),
+            5 => {
+                let bytes = b.get(*at..*at + 4)?;
+                *at += 4;
+                Raw::I32(u32::from_le_bytes(bytes.try_into().ok()?))
+            }
+            _ => return None,
+        };
+        fields.push(RawField {
+            number,
+            offset,
+            value,
+        });
+    }
+    group.is_none().then_some(fields)
+}
+
+/// Split a message into its fields; `None` unless the bytes are exactly a sequence of
+/// well-formed fields.
+fn parse(b: &[u8]) -> Option<Vec<RawField<'_>>> {
+    fields_until(b, &mut 0, None, 0)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn float(f: f64) -> Value {
+    serde_json::Number::from_f64(f)
+        .map(Value::Number)
+        .unwrap_or_else(|| json!(f.to_string()))
+}
+
+fn float32(f: f32) -> Value {
+    // Through the shortest decimal text, so 0.1f32 isn't shown as 0.10000000149011612.
+    float(f.to_string().parse().unwrap_or(f as f64))
+}
+
+#[derive(Debug)]
+struct FieldDef {
+    name: String,
+    /// A scalar type name, or a message or enum name relative to the enclosing message.
+    type_name: String,
+}
+
+/// Messages and enums of a `.proto` file or descriptor set, by full name (`pkg.Outer.Inner`).
+#[derive(Debug, Default)]
+struct Schema {
+    messages: HashMap<String, HashMap<u64, FieldDef>>,
+    enums: HashMap<String, HashMap<i64, String>>,
+    /// Message names in declaration order.
+    order: Vec<String>,
+}
+
+enum Ty {
+    Scalar(&'static str),
+    Message(String),
+    Enum(String),
+}
+
+impl Ty {
+    fn name(&self) -> &str {
+        match self {
+            Ty::Scalar(t) => t,
+            Ty::Message(name) | Ty::Enum(name) => name,
+        }
+    }
+
+    fn wire(&self) -> u64 {
+        match self {
+            Ty::Scalar("double" | "fixed64" | "sfixed64") => 1,
+            Ty::Scalar("float" | "fixed32" | "sfixed32") => 5,
+            Ty::Scalar("string" | "bytes") | Ty::Message(_) => 2,
+            Ty::Scalar(_) | Ty::Enum(_) => 0,
+        }
+    }
+}
+
+fn qualify(scope: &str, name: &str) -> String {
+    if scope.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", scope, name)
+    }
+}
+
+impl Schema {
+    /// Resolve a type reference the way protoc does: from the innermost enclosing scope outwards,
+    /// or absolutely when it starts with a dot.
+    fn field_type(&self, message: &str, def: &FieldDef) -> Option<Ty> {
+        if let Some(scalar) = SCALARS.iter().find(|s| **s == def.type_name) {
+            return Some(Ty::Scalar(scalar));
+        }
+        let found = |full: String| {
+            if self.messages.contains_key(&full) {
+                Some(Ty::Message(full))
+            } else if self.enums.contains_key(&full) {
+                Some(Ty::Enum(full))
+            } else {
+                None
+            }
+        };
+        if let Some(absolute) = def.type_name.strip_prefix('.') {
+            return found(absolute.to_string());
+        }
+        let mut scope = message;
+        loop {
+            if let Some(ty) = found(qualify(scope, &def.type_name)) {
+                return Some(ty);
+            }
+            if scope.is_empty() {
+                return None;
+            }
+            scope = scope.rfind('.').map_or("", |i| &scope[..i]);
+        }
+    }
+
+    /// A message by full name, or by a name the full name ends with (`Inner`, `Outer.Inner`).
+    fn find(&self, name: &str) -> Option<String> {
+        let name = name.trim_start_matches('.');
+        let suffix = format!(".{}", name);
+        self.order
+            .iter()
+            .find(|full| *full == name)
+            .or_else(|| self.order.iter().find(|full| full.ends_with(&suffix)))
+            .cloned()
+    }
+
+    fn scalar(&self, ty: &Ty, v: u64) -> Value {
+        match ty {
+            Ty::Enum(name) => {
+                let number = v as i32 as i64;
+                match self.enums.get(name).and_then(|values| values.get(&number)) {
+                    Some(value) => json!(value),
+                    None => json!(number),
+                }
+            }
+            Ty::Scalar("int64" | "sfixed64") => json!(v as i64),
+            Ty::Scalar("int32" | "sfixed32") => json!(v as i32),
+            Ty::Scalar("uint32" | "fixed32") => json!(v as u32),
+            Ty::Scalar("sint32" | "sint64") => json!((v >> 1) as i64 ^ -((v & 1) as i64)),
+            Ty::Scalar("bool") => json!(v != 0),
+            Ty::Scalar("double") => float(f64::from_bits(v)),
+            Ty::Scalar("float") => float32(f32::from_bits(v as u32)),
+            _ => json!(v),
+        }
+    }
+
+    fn decode(&self, raw: Vec<RawField>, message: Option<&str>, depth: usize) -> Vec<ProtoField> {
+        raw.into_iter()
+            .map(|field| {
+                let def =
+                    message.and_then(|m| Some((m, self.messages.get(m)?.get(&field.number)?)));
+                let ty = def.and_then(|(m, def)| self.field_type(m, def));
+                let wire_type = match field.value {
+                    Raw::Varint(_) => "varint",
+                    Raw::I64(_) => "i64",
+                    Raw::Len(_) => "len",
+                    Raw::Group(_) => "group",
+                    Raw::I32(_) => "i32",
+                };
+                let (kind, value, fields) = self.value(field.value, ty, depth);
+                ProtoField {
+                    number: field.number,
+                    name: def.map(|(_, def)| def.name.clone()),
+                    wire_type,
+                    kind,
+                    value,
+                    fields,
+                    offset: field.offset,
+                }
+            })
+            .collect()
+    }
+
+    fn value(&self, raw: Raw, ty: Option<Ty>, depth: usize) -> (String, Value, Vec<ProtoField>) {
+        let wire = match raw {
+            Raw::Varint(_) => 0,
+            Raw::I64(_) => 1,
+            Raw::Len(_) => 2,
+            Raw::Group(_) => 3,
+            Raw::I32(_) => 5,
+        };
+        match (raw, ty) {
+            (Raw::Group(fields), ty) => {
+                let message = match ty {
+                    Some(Ty::Message(name)) => Some(name),
+                    _ => None,
+                };
+                let fields = self.decode(fields, message.as_deref(), depth + 1);
+                let kind = message.unwrap_or_else(|| "group".to_string());
+                (kind, Value::Null, fields)
+            }
+            (Raw::Len(b), Some(Ty::Message(name))) => match parse(b) {
+                Some(fields) if depth < MAX_DEPTH => {
+                    let fields = self.decode(fields, Some(&name), depth + 1);
+                    (name, Value::Null, fields)
+                }
+                _ => ("bytes".to_string(), json!(to_hex(b)), Vec::new()),
+            },
+            (Raw::Len(b), Some(Ty::Scalar("string"))) => (
+                "string".to_string(),
+                json!(String::from_utf8_lossy(b)),
+                Vec::new(),
+            ),
+            (Raw::Len(b), Some(Ty::Scalar("bytes"))) => {
+                ("bytes".to_string(), json!(to_hex(b)), Vec::new())
+            }
+            (Raw::Len(b), Some(ty)) => match packed(ty.wire(), b) {
+                Some(values) => {
+                    let values = values.into_iter().map(|v| self.scalar(&ty, v)).collect();
+                    (
+                        format!("packed {}", ty.name()),
+                        Value::Array(values),
+                        Vec::new(),
+                    )
+                }
+                None => self.guess_len(b, depth),
+            },
+            (Raw::Len(b), None) => self.guess_len(b, depth),
+            (Raw::Varint(v) | Raw::I64(v), Some(ty)) if ty.wire() == wire => {
+                (ty.name().to_string(), self.scalar(&ty, v), Vec::new())
+            }
+            (Raw::I32(v), Some(ty)) if ty.wire() == wire => (
+                ty.name().to_string(),
+                self.scalar(&ty, v as u64),
+                Vec::new(),
+            ),
+            (Raw::Varint(v), _) => ("varint".to_string(), json!(v), Vec::new()),
+            (Raw::I64(v), _) => {
+                let f = f64::from_bits(v);
+                if f.is_normal() && (1e-7..1e15).contains(&f.abs()) {
+                    ("double".to_string(), float(f), Vec::new())
+                } else {
+                    ("fixed64".to_string(), json!(v), Vec::new())
+                }
+            }
+            (Raw::I32(v), _) => {
+                let f = f32::from_bits(v);
+                if f.is_normal() && (1e-5..1e9).contains(&f.abs()) {
+                    ("float".to_string(), float32(f), Vec::new())
+                } else {
+                    ("fixed32".to_string(), json!(v), Vec::new())
+                }
+            }
+        }
+    }
+
+    /// Without a schema a length-delimited field is text if it reads as text, else a message if
+    /// it parses as one, else bytes.
+    fn guess_len(&self, b: &[u8], depth: usize) -> (String, Value, Vec<ProtoField>) {
+        if b.is_empty() {
+            return ("string".to_string(), json!(""), Vec::new());
+        }
+        if let Some(text) = as_text(b) {
+            return ("string".to_string(), json!(text), Vec::new());
+        }
+        match parse(b) {
+            Some(fields) if depth < MAX_DEPTH => (
+                "message".to_string(),
+                Value::Null,
+                self.decode(fields, None, depth + 1),
+            ),
+            _ => ("bytes".to_string(), json!(to_hex(b)), Vec::new()),
+        }
+    }
+}
+
+/// Values of a packed repeated field of the given wire type.
+fn packed(wire: u64, b: &[u8]) -> Option<Vec<u64>> {
+    match wire {
+        0 => {
+            let mut at = 0;
+            let mut values = Vec::new();
+            while at < b.len() {
+                values.push(varint(b, &mut at)?);
+            }
+            Some(values)
+        }
+        1 if b.len().is_multiple_of(8) => Some(
+            b.chunks_exact(8)
+                .map(|c| u64::from_le_bytes(c.try_into().unwrap()))
+                .collect(),
+        ),
+        5 if b.len().is_multiple_of(4) => Some(
+            b.chunks_exact(4)
+                .map(|c| u32::from_le_bytes(c.try_into().unwrap()) as u64)
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+fn len_text(field: &RawField) -> Option<String> {
+    match field.value {
+        Raw::Len(b) => String::from_utf8(b.to_vec()).ok(),
+        _ => None,
+    }
+}
+
+fn varint_value(field: &RawField) -> Option<u64> {
+    match field.value {
+        Raw::Varint(v) => Some(v),
+        _ => None,
+    }
+}
+
+fn get<'f, 'a>(fields: &'f [RawField<'a>], number: u64) -> Option<&'f RawField<'a>> {
+    fields.iter().find(|f| f.number == number)
+}
+
+fn descriptor_enum(b: &[u8], scope: &str, schema: &mut Schema) -> Option<()> {
+    let fields = parse(b)?;
+    let name = qualify(scope, &get(&fields, 1).and_then(len_text)?);
+    let mut values = HashMap::new();
+    for field in fields.iter().filter(|f| f.number == 2) {
+        let Raw::Len(value) = field.value else {
+            return None;
+        };
+        let value = parse(value)?;
+        let number = get(&value, 2).and_then(varint_value).unwrap_or(0);
+        values.insert(number as i32 as i64, get(&value, 1).and_then(len_text)?);
+    }
+    schema.enums.insert(name, values);
+    Some(())
+}
+
+fn descriptor_message(b: &[u8], scope: &str, schema: &mut Schema) -> Option<()> {
+    let fields = parse(b)?;
+    let name = qualify(scope, &get(&fields, 1).and_then(len_text)?);
+    schema.order.push(name.clone());
+    let mut defs = HashMap::new();
+    for field in &fields {
+        match (field.number, &field.value) {
+            (2, Raw::Len(field)) => {
+                let field = parse(field)?;
+                let number = get(&field, 3).and_then(varint_value)?;
+                let type_name = match get(&field, 6).and_then(len_text) {
+                    Some(type_name) => type_name,
+                    None => {
+                        let ty = get(&field, 5).and_then(varint_value).unwrap_or(0);
+                        DESCRIPTOR_TYPES.get(ty as usize)?.to_string()
+                    }
+                };
+                let name = get(&field, 1).and_then(len_text)?;
+                defs.insert(number, FieldDef { name, type_name });
+            }
+            (3, Raw::Len(nested)) => descriptor_message(nested, &name, schema)?,
+            (4, Raw::Len(nested)) => descriptor_enum(nested, &name, schema)?,
+            _ => {}
+        }
+    }
+    schema.messages.insert(name, defs);
+    Some(())
+}
+
+/// A `FileDescriptorSet`, as written by `protoc --descriptor_set_out`.
+fn descriptor_set(b: &[u8]) -> Option<Schema> {
+    let mut schema = Schema::default();
+    for file in parse(b)? {
+        let Raw::Len(file) = file.value else {
+            return None;
+        };
+        let fields = parse(file)?;
+        let package = get(&fields, 2).and_then(len_text).unwrap_or_default();
+        for field in &fields {
+            match (field.number, &field.value) {
+                (4, Raw::Len(message)) => descriptor_message(message, &package, &mut schema)?,
+                (5, Raw::Len(nested)) => descriptor_enum(nested, &package, &mut schema)?,
+                _ => {}
+            }
+        }
+    }
+    (!schema.order.is_empty()).then_some(schema)
+}
+
+/// Identifiers, numbers, string literals and punctuation of a `.proto` file, without comments.
+fn proto_tokens(src: &str) -> Vec<String> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+        } else if c == '"' || c == '\'' {
+            let start = i;
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                i += if chars[i] == '\\' { 2 } else { 1 };
+            }
+            i += 1;
+            tokens.push(chars[start..i.min(chars.len())].iter().collect());
+        } else if c.is_alphanumeric() || matches!(c, '_' | '.' | '-') {
+            let start = i;
+            i += 1;
+            while i < chars.len() && (chars[i].is_alphanumeric() || matches!(chars[i], '_' | '.')) {
+                i += 1;
+            }
+            tokens.push(chars[start..i].iter().collect());
+        } else {
+            tokens.push(c.to_string());
+            i += 1;
+        }
+    }
+    tokens
+}
+
+/// Reads the messages, fields and enums of a `.proto` file; services, options and extensions
+/// are skipped.
+struct ProtoParser {
+    tokens: Vec<String>,
+    at: usize,
+    schema: Schema,
+}
+
+impl ProtoParser {
+    fn next(&mut self) -> Result<String, String> {
+        let token = self
+            .tokens
+            .get(self.at)
+            .cloned()
+            .ok_or("Unexpected end of .proto file.")?;
+        self.at += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), String> {
+        let token = self.next()?;
+        if token == expected {
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected `{}` but found `{}` in .proto file.",
+                expected, token
+            ))
+        }
+    }
+
+    fn number(&mut self) -> Result<i64, String> {
+        let token = self.next()?;
+        token
+            .parse()
+            .map_err(|_| format!("Expected a number but found `{}` in .proto file.", token))
+    }
+
+    /// Skip to the end of a statement (`;`), including any `{ ... }` option values on the way.
+    fn skip_statement(&mut self) {
+        let mut depth = 0;
+        while let Some(token) = self.tokens.get(self.at) {
+            self.at += 1;
+            match token.as_str() {
+                ";" if depth == 0 => return,
+                "{" => depth += 1,
+                "}" if depth == 0 => {
+                    self.at -= 1;
+                    return;
+                }
+                "}" => depth -= 1,
+                _ => {}
+            }
+        }
+    }
+
+    /// Skip a declaration with a body (`service`, `extend`), up to its closing brace.
+    fn skip_block(&mut self) {
+        let mut depth = 0;
+        while let Some(token) = self.tokens.get(self.at) {
+            self.at += 1;
+            match token.as_str() {
+                "{" => depth += 1,
+                "}" if depth <= 1 => return,
+                "}" => depth -= 1,
+                _ => {}
+            }
+        }
+    }
+
+    fn message(&mut self, scope: &str) -> Result<(), String> {
+        let name = qualify(scope, &self.next()?);
+        self.expect("{")?;
+        self.message_body(name)
+    }
+
+    fn message_body(&mut self, name: String) -> Result<(), String> {
+        self.schema.order.push(name.clone());
+        let mut defs = HashMap::new();
+        loop {
+            let token = self.next()?;
+            if token == "}" {
+                break;
+            }
+            self.member(token, &name, &mut defs)?;
+        }
+        self.schema.messages.insert(name, defs);
+        Ok(())
+    }
+
+    fn member(
+        &mut self,
+        token: String,
+        message: &str,
+        defs: &mut HashMap<u64, FieldDef>,
+    ) -> Result<(), String> {
+        match token.as_str() {
+            ";" => {}
+            "message" => self.message(message)?,
+            "enum" => self.enumeration(message)?,
+            "extend" => self.skip_block(),
+            "option" | "reserved" | "extensions" => self.skip_statement(),
+            "optional" | "required" | "repeated" => {
+                let token = self.next()?;
+                self.member(token, message, defs)?;
+            }
+            "oneof" => {
+                self.next()?;
+                self.expect("{")?;
+                loop {
+                    let token = self.next()?;
+                    if token == "}" {
+                        break;
+                    }
+                    self.member(token, message, defs)?;
+                }
+            }
+            "map" => {
+                self.expect("<")?;
+                let key = self.next()?;
+                self.expect(",")?;
+                let value = self.next()?;
+                self.expect(">")?;
+                let name = self.next()?;
+                self.expect("=")?;
+                let number = self.number()? as u64;
+                self.skip_statement();
+                let entry = format!("{}.{}Entry", message, name);
+                let fields = [(1, "key", key), (2, "value", value)]
+                    .into_iter()
+                    .map(|(number, name, type_name)| {
+                        let name = name.to_string();
+                        (number, FieldDef { name, type_name })
+                    })
+                    .collect();
+                self.schema.messages.insert(entry.clone(), fields);
+                let type_name = format!(".{}", entry);
+                defs.insert(number, FieldDef { name, type_name });
+            }
+            "group" => {
+                let name = self.next()?;
+                self.expect("=")?;
+                let number = self.number()? as u64;
+                while self.next()? != "{" {}
+                let full = qualify(message, &name);
+                self.message_body(full.clone())?;
+                let type_name = format!(".{}", full);
+                let name = name.to_lowercase();
+                defs.insert(number, FieldDef { name, type_name });
+            }
+            _ => {
+                let name = self.next()?;
+                self.expect("=")?;
+                let number = self.number()? as u64;
+                self.skip_statement();
+                defs.insert(
+                    number,
+                    FieldDef {
+                        name,
+                        type_name: token,
+                    },
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn enumeration(&mut self, scope: &str) -> Result<(), String> {
+        let name = qualify(scope, &self.next()?);
+        self.expect("{")?;
+        let mut values = HashMap::new();
+        loop {
+            let token = self.next()?;
+            match token.as_str() {
+                "}" => break,
+                ";" => {}
+                "option" | "reserved" => self.skip_statement(),
+                _ => {
+                    self.expect("=")?;
+                    let number = self.number()?;
+                    self.skip_statement();
+                    values.insert(number, token);
+                }
+            }
+        }
+        self.schema.enums.insert(name, values);
+        Ok(())
+    }
+}
+
+fn parse_proto(src: &str) -> Result<Schema, String> {
+    let mut parser = ProtoParser {
+        tokens: proto_tokens(src),
+        at: 0,
+        schema: Schema::default(),
+    };
+    let mut package = String::new();
+    while parser.at < parser.tokens.len() {
+        let token = parser.next()?;
+        match token.as_str() {
+            "package" => {
+                package = parser.next()?;
+                parser.skip_statement();
+            }
+            "message" => parser.message(&package)?,
+            "enum" => parser.enumeration(&package)?,
+            "service" | "extend" => parser.skip_block(),
+            ";" => {}
+            _ => parser.skip_statement(),
+        }
+    }
+    Ok(parser.schema)
+}
+
+fn load_schema(path: &str) -> Result<Schema, String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    if path.ends_with(".proto") {
+        parse_proto(&String::from_utf8_lossy(&bytes))
+    } else {
+        descriptor_set(&bytes).ok_or_else(|| "Not a .proto file or descriptor set.".to_string())
+    }
+}
+
+/// Frames of a gRPC message stream (a compressed flag and a big-endian length before each
+/// message), if the whole payload is made of them.
+fn grpc_frames(b: &[u8]) -> Option<Vec<Vec<u8>>> {
+    let mut frames = Vec::new();
+    let mut at = 0;
+    while at < b.len() {
+        let compressed = *b.get(at)?;
+        let len = u32::from_be_bytes(b.get(at + 1..at + 5)?.try_into().ok()?) as usize;
+        let body = b.get(at + 5..at + 5 + len)?;
+        frames.push(match compressed {
+            0 => body.to_vec(),
+            1 => decompress(body)?.1,
+            _ => return None,
+        });
+        at += 5 + len;
+    }
+    (!frames.is_empty()).then_some(frames)
+}
+
+fn write_text(fields: &[ProtoField], indent: usize, out: &mut String) {
+    for field in fields {
+        let pad = "  ".repeat(indent);
+        let label = match &field.name {
+            Some(name) => name.clone(),
+            None => field.number.to_string(),
+        };
+        match &field.value {
+            Value::Null => {
+                out.push_str(&format!("{}{} {{\n", pad, label));
+                write_text(&field.fields, indent + 1, out);
+                out.push_str(&format!("{}}}\n", pad));
+            }
+            Value::String(s) if field.kind == "bytes" => {
+                out.push_str(&format!("{}{}: 0x{}\n", pad, label, s))
+            }
+            Value::String(s) if field.kind != "string" => {
+                out.push_str(&format!("{}{}: {}\n", pad, label, s))
+            }
+            value => out.push_str(&format!("{}{}: {}\n", pad, label, value)),
+        }
+    }
+}
+
+/// Decode protobuf bytes from a file (`path`) or from hex or base64 text (`data`). A `.proto`
+/// file or descriptor set given as `schema` supplies field names and types for `message_type`
+/// (by default the first message it declares).
+#[tauri::command]
+pub fn decode_protobuf(
+    path: Option<String>,
+    data: Option<String>,
+    schema: Option<String>,
+    message_type: Option<String>,
+) -> Result<ProtobufDecoding, String> {
+    let bytes = match (path, data) {
+        (Some(path), _) => std::fs::read(&path).map_err(|e| e.to_string())?,
+        (None, Some(data)) => {
+            let data = data.trim();
+            hex(data)
+                .or_else(|| base64(&data.split_whitespace().collect::<String>()).map(|(_, b)| b))
+                .ok_or("The selection is not hex or base64 data.")?
+        }
+        (None, None) => return Err("No protobuf data given.".to_string()),
+    };
+    let schema = match schema {
+        Some(path) => load_schema(&path)?,
+        None => Schema::default(),
+    };
+    let message_type = match message_type {
+        Some(name) => Some(
+            schema
+                .find(&name)
+                .ok_or_else(|| format!("Message type {} not found in the schema.", name))?,
+        ),
+        None => schema.order.first().cloned(),
+    };
+    let frames = grpc_frames(&bytes);
+    let grpc = frames.is_some();
+    let messages = frames
+        .unwrap_or_else(|| vec![bytes])
+        .iter()
+        .map(|frame| {
+            let fields = parse(frame).ok_or("Not valid protobuf wire format.")?;
+            Ok(schema.decode(fields, message_type.as_deref(), 0))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let text = messages
+        .iter()
+        .map(|fields| {
+            let mut text = String::new();
+            write_text(fields, 0, &mut text);
+            text
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    Ok(ProtobufDecoding {
+        message_type,
+        grpc,
+        messages,
+        text,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn len_field(number: u8, body: &[u8]) -> Vec<u8> {
+        let mut out = vec![number << 3 | 2, body.len() as u8];
+        out.extend_from_slice(body);
+        out
+    }
+
+    #[test]
+    fn decode_protobuf_guesses_types_without_schema() {
+        let out = decode_protobuf(
+            None,
+            Some("08 96 01 12 07 74 65 73 74 69 6e 67 1a 03 08 96 01 21 00 00 00 00 00 00 f8 3f 2a 02 ff 00".to_string()),
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(!out.grpc);
+        assert_eq!(
+            out.text,
+            "1: 150\n2: \"testing\"\n3 {\n  1: 150\n}\n4: 1.5\n5: 0xff00\n"
+        );
+        let kinds: Vec<(&str, &str)> = out.messages[0]
+            .iter()
+            .map(|f| (f.wire_type, f.kind.as_str()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("varint", "varint"),
+                ("len", "string"),
+                ("len", "message"),
+                ("i64", "double"),
+                ("len", "bytes")
+            ]
+        );
+        assert_eq!(out.messages[0][2].offset, 12);
+        assert!(decode_protobuf(None, Some("0a 05 01".to_string()), None, None).is_err());
+    }
+
+    #[test]
+    fn decode_protobuf_names_fields_from_proto_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let proto = dir.path().join("order.proto");
+        std::fs::write(
+            &proto,
+            r#"syntax = "proto3";
+            package demo;
+            // An order.
+            message Order {
+              int64 id = 1;
+              string name = 2 [json_name = "title"];
+              Item item = 3;
+              repeated sint32 deltas = 4;
+              Status status = 5;
+              map<string, int32> counts = 6;
+              message Item { double price = 1; }
+              enum Status { UNKNOWN = 0; PAID = 2; }
+              /* reserved */ reserved 7 to 9;
+            }
+            service Orders { rpc Get (Order) returns (Order) {} }"#,
+        )
+        .unwrap();
+        let mut bytes = vec![0x08, 0x96, 0x01];
+        bytes.extend(len_field(2, b"hi"));
+        bytes.extend(len_field(3, &[0x09, 0, 0, 0, 0, 0, 0, 0xf8, 0x3f]));
+        bytes.extend(len_field(4, &[0x01, 0x02, 0x03]));
+        bytes.extend([0x28, 0x02]);
+        bytes.extend(len_field(6, &[0x0a, 0x01, b'a', 0x10, 0x07]));
+        let data = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        let out = decode_protobuf(
+            None,
+            Some(data),
+            Some(proto.to_string_lossy().into_owned()),
+            None,
+        )
+        .unwrap();
+        assert_eq!(out.message_type.as_deref(), Some("demo.Order"));
+        assert_eq!(
+            out.text,
+            "id: 150\nname: \"hi\"\nitem {\n  price: 1.5\n}\ndeltas: [-1,1,-2]\nstatus: PAID\n\
+             counts {\n  key: \"a\"\n  value: 7\n}\n"
+        );
+        assert_eq!(out.messages[0][2].kind, "demo.Order.Item");
+        assert_eq!(out.messages[0][3].kind, "packed sint32");
+    }
+
+    #[test]
+    fn decode_protobuf_reads_grpc_frames_with_descriptor_set() {
+        let field = [len_field(1, b"seq"), vec![0x18, 0x01, 0x28, 0x05]].concat();
+        let message = [len_field(1, b"Ping"), len_field(2, &field)].concat();
+        let file = [
+            len_field(1, b"ping.proto"),
+            len_field(2, b"net"),
+            len_field(4, &message),
+        ]
+        .concat();
+        let dir = tempfile::tempdir().unwrap();
+        let descriptors = dir.path().join("ping.pb");
+        std::fs::write(&descriptors, len_field(1, &file)).unwrap();
+        // Two frames: seq = 7 and seq = -1 (a ten-byte varint).
+        let frames = "AAAAAAIIBwAAAAALCP///////////wE=";
+        let out = decode_protobuf(
+            None,
+            Some(frames.to_string()),
+            Some(descriptors.to_string_lossy().into_owned()),
+            Some("Ping".to_string()),
+        )
+        .unwrap();
+        assert!(out.grpc);
+        assert_eq!(out.message_type.as_deref(), Some("net.Ping"));
+        assert_eq!(out.text, "seq: 7\n\nseq: -1\n");
+        assert!(decode_protobuf(
+            None,
+            Some(frames.to_string()),
+            Some(descriptors.to_string_lossy().into_owned()),
+            Some("Pong".to_string()),
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,475 @@
+//! MessagePack, CBOR and BSON decoded to JSON. Values JSON can't express are written as objects
+//! with a `$type` key: `{"$type": "binary", "base64": ..}`, `{"$type": "date", "value": ..}`,
+//! `{"$type": "tag", "tag": .., "value": ..}`, `{"$type": "objectid", "value": ..}`, ...
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, SecondsFormat};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::format_json;
+
+/// A decoded file.
+#[derive(Debug, Serialize)]
+pub struct SerializedDocument {
+    /// `msgpack`, `cbor` or `bson`.
+    pub format: String,
+    /// Number of top-level values; several are shown as a JSON array.
+    pub documents: usize,
+    /// The decoded value(s) as pretty-printed JSON.
+    pub json: String,
+}
+
+fn typed(kind: &str, fields: Value) -> Value {
+    let mut map = Map::new();
+    map.insert("$type".to_string(), json!(kind));
+    if let Value::Object(fields) = fields {
+        map.extend(fields);
+    }
+    Value::Object(map)
+}
+
+fn binary(bytes: &[u8]) -> Value {
+    typed(
+        "binary",
+        json!({ "size": bytes.len(), "base64": BASE64.encode(bytes) }),
+    )
+}
+
+fn float(f: f64) -> Value {
+    serde_json::Number::from_f64(f)
+        .map(Value::Number)
+        .unwrap_or_else(|| typed("float", json!({ "value": f.to_string() })))
+}
+
+fn date(secs: i64, nanos: u32) -> Value {
+    match DateTime::from_timestamp(secs, nanos) {
+        Some(d) => typed(
+            "date",
+            json!({ "value": d.to_rfc3339_opts(SecondsFormat::AutoSi, true) }),
+        ),
+        None => typed("date", json!({ "seconds": secs, "nanos": nanos })),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// JSON object key for a map key that may not be a string.
+fn key(value: Value) -> String {
+    match value {
+        Value::String(s) => s,
+        other => other.to_string(),
+    }
+}
+
+fn msgpack_json(value: rmpv::Value) -> Value {
+    use rmpv::Value as V;
+    match value {
+        V::Nil => Value::Null,
+        V::Boolean(b) => json!(b),
+        V::Integer(i) => i
+            .as_i64()
+            .map(|n| json!(n))
+            .or_else(|| i.as_u64().map(|n| json!(n)))
+            .unwrap_or(Value::Null),
+        V::F32(f) => float(f as f64),
+        V::F64(f) => float(f),
+        V::String(s) => match s.into_str() {
+            Some(s) => json!(s),
+            None => typed("invalid-utf8", json!({})),
+        },
+        V::Binary(b) => binary(&b),
+        V::Array(items) => Value::Array(items.into_iter().map(msgpack_json).collect()),
+        V::Map(entries) => Value::Object(
+            entries
+                .into_iter()
+                .map(|(k, v)| (key(msgpack_json(k)), msgpack_json(v)))
+                .collect(),
+        ),
+        // Timestamp extension: 32-bit seconds, 30-bit nanos + 34-bit seconds, or 32-bit nanos + 64-bit seconds.
+        V::Ext(-1, data) => match data.len() {
+            4 => date(u32::from_be_bytes(data[..4].try_into().unwrap()) as i64, 0),
+            8 => {
+                let n = u64::from_be_bytes(data[..8].try_into().unwrap());
+                date((n & 0x3_ffff_ffff) as i64, (n >> 34) as u32)
+            }
+            12 => date(
+                i64::from_be_bytes(data[4..12].try_into().unwrap()),
+                u32::from_be_bytes(data[..4].try_into().unwrap()),
+            ),
+            _ => typed("ext", json!({ "ext": -1, "base64": BASE64.encode(&data) })),
+        },
+        V::Ext(kind, data) => typed(
+            "ext",
+            json!({ "ext": kind, "base64": BASE64.encode(&data) }),
+        ),
+    }
+}
+
+fn cbor_json(value: ciborium::Value) -> Value {
+    use ciborium::Value as V;
+    match value {
+        V::Null => Value::Null,
+        V::Bool(b) => json!(b),
+        V::Integer(i) => {
+            let n = i128::from(i);
+            i64::try_from(n)
+                .map(|n| json!(n))
+                .or_else(|_| u64::try_from(n).map(|n| json!(n)))
+                .unwrap_or_else(|_| typed("bigint", json!({ "value": n.to_string() })))
+        }
+        V::Float(f) => float(f),
+        V::Text(s) => json!(s),
+        V::Bytes(b) => binary(&b),
+        V::Array(items) => Value::Array(items.into_iter().map(cbor_json).collect()),
+        V::Map(entries) => Value::Object(
+            entries
+                .into_iter()
+                .map(|(k, v)| (key(cbor_json(k)), cbor_json(v)))
+                .collect(),
+        ),
+        V::Tag(0, inner) => match *inner {
+            V::Text(s) => typed("date", json!({ "value": s })),
+            other => typed("tag", json!({ "tag": 0, "value": cbor_json(other) })),
+        },
+        V::Tag(1, inner) => match *inner {
+            V::Integer(i) => date(i128::from(i) as i64, 0),
+            V::Float(f) => date(f.floor() as i64, ((f - f.floor()) * 1e9) as u32),
+            other => typed("tag", json!({ "tag": 1, "value": cbor_json(other) })),
+        },
+        // Bignums: big-endian magnitude; tag 3 is -1 - n.
+        V::Tag(tag @ (2 | 3), inner) => match *inner {
+            V::Bytes(b) if b.len() < 16 => {
+                let n = b.iter().fold(0u128, |n, &byte| (n << 8) | byte as u128);
+                let value = if tag == 2 {
+                    n.to_string()
+                } else {
+                    format!("-{}", n + 1)
+                };
+                typed("bigint", json!({ "value": value }))
+            }
+            other => typed("tag", json!({ "tag": tag, "value": cbor_json(other) })),
+        },
+        V::Tag(37, inner) => match *inner {
+            V::Bytes(b) if b.len() == 16 => typed("uuid", json!({ "value": uuid(&b) })),
+            other => typed("tag", json!({ "tag": 37, "value": cbor_json(other) })),
+        },
+        V::Tag(tag, inner) => typed("tag", json!({ "tag": tag, "value": cbor_json(*inner) })),
+        _ => Value::Null,
+    }
+}
+
+fn uuid(b: &[u8]) -> String {
+    let h = hex(b);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &h[..8],
+        &h[8..12],
+        &h[12..16],
+        &h[16..20],
+        &h[20..]
+    )
+}
+
+/// Reader over a BSON document.
+struct Bson<'a> {
+    b: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Bson<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let bytes = self
+            .b
+            .get(self.pos..self.pos + n)
+            .ok_or_else(|| format!("Truncated BSON at byte {}", self.pos))?;
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn i64(&mut self) -> Result<i64, String> {
+        Ok(i64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn cstring(&mut self) -> Result<String, String> {
+        let len = self.b[self.pos..]
+            .iter()
+            .position(|&c| c == 0)
+            .ok_or("Unterminated BSON key")?;
+        let s = String::from_utf8_lossy(self.take(len)?).into_owned();
+        self.pos += 1;
+        Ok(s)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let len = self.i32()?;
+        if len < 1 {
+            return Err(format!("Bad BSON string length at byte {}", self.pos));
+        }
+        let bytes = self.take(len as usize)?;
+        Ok(String::from_utf8_lossy(&bytes[..bytes.len() - 1]).into_owned())
+    }
+
+    /// A document as (key, value) pairs.
+    fn document(&mut self) -> Result<Vec<(String, Value)>, String> {
+        let start = self.pos;
+        let len = self.i32()?;
+        if len < 5 || start + len as usize > self.b.len() {
+            return Err(format!("Bad BSON document length at byte {}", start));
+        }
+        let end = start + len as usize - 1;
+        let mut fields = Vec::new();
+        while self.pos < end {
+            let kind = self.take(1)?[0];
+            let name = self.cstring()?;
+            fields.push((name, self.element(kind)?));
+        }
+        if self.take(1)? != [0] {
+            return Err(format!("BSON document at byte {} is not terminated", start));
+        }
+        Ok(fields)
+    }
+
+    fn element(&mut self, kind: u8) -> Result<Value, String> {
+        Ok(match kind {
+            0x01 => float(f64::from_le_bytes(self.take(8)?.try_into().unwrap())),
+            0x02 => json!(self.string()?),
+            0x03 => Value::Object(self.document()?.into_iter().collect()),
+            0x04 => Value::Array(self.document()?.into_iter().map(|(_, v)| v).collect()),
+            0x05 => {
+                let len = self.i32()?.max(0) as usize;
+                let subtype = self.take(1)?[0];
+                let data = self.take(len)?;
+                match subtype {
+                    0x04 if len == 16 => typed("uuid", json!({ "value": uuid(data) })),
+                    0x00 => binary(data),
+                    _ => typed(
+                        "binary",
+                        json!({ "subtype": subtype, "size": len, "base64": BASE64.encode(data) }),
+                    ),
+                }
+            }
+            0x06 => typed("undefined", json!({})),
+            0x07 => typed("objectid", json!({ "value": hex(self.take(12)?) })),
+            0x08 => json!(self.take(1)?[0] != 0),
+            0x09 => {
+                let ms = self.i64()?;
+                date(
+                    ms.div_euclid(1000),
+                    (ms.rem_euclid(1000) * 1_000_000) as u32,
+                )
+            }
+            0x0a => Value::Null,
+            0x0b => {
+                let pattern = self.cstring()?;
+                typed(
+                    "regex",
+                    json!({ "pattern": pattern, "options": self.cstring()? }),
+                )
+            }
+            0x0c => {
+                let namespace = self.string()?;
+                typed(
+                    "dbpointer",
+                    json!({ "namespace": namespace, "id": hex(self.take(12)?) }),
+                )
+            }
+            0x0d => typed("javascript", json!({ "code": self.string()? })),
+            0x0e => typed("symbol", json!({ "value": self.string()? })),
+            0x0f => {
+                self.i32()?;
+                let code = self.string()?;
+                let scope: Map<String, Value> = self.document()?.into_iter().collect();
+                typed("javascript", json!({ "code": code, "scope": scope }))
+            }
+            0x10 => json!(self.i32()?),
+            0x11 => {
+                let increment = u32::from_le_bytes(self.take(4)?.try_into().unwrap());
+                let seconds = u32::from_le_bytes(self.take(4)?.try_into().unwrap());
+                typed("timestamp", json!({ "t": seconds, "i": increment }))
+            }
+            0x12 => json!(self.i64()?),
+            0x13 => typed("decimal128", json!({ "hex": hex(self.take(16)?) })),
+            0xff => typed("minkey", json!({})),
+            0x7f => typed("maxkey", json!({})),
+            other => {
+                return Err(format!(
+                    "Unknown BSON element type 0x{:02x} at byte {}",
+                    other, self.pos
+                ))
+            }
+        })
+    }
+}
+
+fn decode_all(bytes: &[u8], format: &str) -> Result<Vec<Value>, String> {
+    let mut values = Vec::new();
+    match format {
+        "msgpack" => {
+            let mut rest = bytes;
+            while !rest.is_empty() {
+                values.push(msgpack_json(
+                    rmpv::decode::read_value(&mut rest).map_err(|e| e.to_string())?,
+                ));
+            }
+        }
+        "cbor" => {
+            let mut rest = bytes;
+            while !rest.is_empty() {
+                let value: ciborium::Value =
+                    ciborium::de::from_reader(&mut rest).map_err(|e| e.to_string())?;
+                values.push(cbor_json(value));
+            }
+        }
+        "bson" => {
+            let mut reader = Bson { b: bytes, pos: 0 };
+            while reader.pos < bytes.len() {
+                values.push(Value::Object(reader.document()?.into_iter().collect()));
+            }
+        }
+        other => return Err(format!("Unsupported format: {}", other)),
+    }
+    Ok(values)
+}
+
+/// Which of MessagePack, CBOR or BSON the bytes are, judging by a top-level document, map or
+/// array that decodes cleanly to the end.
+pub fn sniff(bytes: &[u8]) -> Option<&'static str> {
+    let decodes = |format| decode_all(bytes, format).is_ok_and(|values| !values.is_empty());
+    let first = *bytes.first()?;
+    let bson_len = bytes
+        .get(..4)
+        .map(|b| i32::from_le_bytes(b.try_into().unwrap()) as usize);
+    if bson_len.is_some_and(|n| n >= 5 && n <= bytes.len() && bytes[n - 1] == 0) && decodes("bson")
+    {
+        return Some("bson");
+    }
+    if (bytes.starts_with(&[0xd9, 0xd9, 0xf7]) || (0xa0..=0xbf).contains(&first)) && decodes("cbor")
+    {
+        return Some("cbor");
+    }
+    if matches!(first, 0x80..=0x9f | 0xdc..=0xdf) && decodes("msgpack") {
+        return Some("msgpack");
+    }
+    None
+}
+
+/// Decode a MessagePack, CBOR or BSON file to JSON. `format` is sniffed from the content when not
+/// given.
+#[tauri::command]
+pub fn decode_serialized(
+    path: String,
+    format: Option<String>,
+) -> Result<SerializedDocument, String> {
+    let bytes = std::fs::read(&path).map_err(|e| e.to_string())?;
+    let format = match format {
+        Some(format) => format,
+        None => sniff(&bytes)
+            .ok_or("Not a MessagePack, CBOR or BSON file.")?
+            .to_string(),
+    };
+    let mut values = decode_all(&bytes, &format)?;
+    let documents = values.len();
+    let value = if documents == 1 {
+        values.remove(0)
+    } else {
+        Value::Array(values)
+    };
+    Ok(SerializedDocument {
+        format,
+        documents,
+        json: format_json(value.to_string(), None)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn decode(bytes: &[u8]) -> (String, String) {
+        let format = sniff(bytes).unwrap();
+        let values = decode_all(bytes, format).unwrap();
+        (format.to_string(), Value::Array(values).to_string())
+    }
+
+    #[test]
+    fn msgpack_decodes_with_binary_and_timestamp_annotations() {
+        let value = rmpv::Value::Map(vec![
+            ("id".into(), 7.into()),
+            ("blob".into(), rmpv::Value::Binary(vec![1, 2, 3])),
+            (
+                "at".into(),
+                rmpv::Value::Ext(-1, 1_714_557_600u32.to_be_bytes().to_vec()),
+            ),
+            (1.into(), rmpv::Value::F64(f64::NAN)),
+        ]);
+        let mut bytes = Vec::new();
+        rmpv::encode::write_value(&mut bytes, &value).unwrap();
+        let (format, json) = decode(&bytes);
+        assert_eq!(format, "msgpack");
+        assert_eq!(
+            json,
+            r#"[{"id":7,"blob":{"$type":"binary","size":3,"base64":"AQID"},"at":{"$type":"date","value":"2024-05-01T10:00:00Z"},"1":{"$type":"float","value":"NaN"}}]"#
+        );
+    }
+
+    #[test]
+    fn cbor_decodes_tags_and_bignums() {
+        use ciborium::Value as V;
+        let value = V::Map(vec![
+            (
+                V::Text("when".into()),
+                V::Tag(1, Box::new(V::Integer(1_714_557_600.into()))),
+            ),
+            (
+                V::Text("big".into()),
+                V::Tag(2, Box::new(V::Bytes(vec![1, 0, 0, 0, 0, 0, 0, 0, 0]))),
+            ),
+            (
+                V::Text("uri".into()),
+                V::Tag(32, Box::new(V::Text("https://example.com".into()))),
+            ),
+        ]);
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(&value, &mut bytes).unwrap();
+        let (format, json) = decode(&bytes);
+        assert_eq!(format, "cbor");
+        assert_eq!(
+            json,
+            r#"[{"when":{"$type":"date","value":"2024-05-01T10:00:00Z"},"big":{"$type":"bigint","value":"18446744073709551616"},"uri":{"$type":"tag","tag":32,"value":"https://example.com"}}]"#
+        );
+    }
+
+    #[test]
+    fn bson_documents_decode_and_file_flows_through_format_json() {
+        let mut doc = Vec::new();
+        doc.extend([0x07]);
+        doc.extend(b"_id\0");
+        doc.extend([0x66, 0x31, 0x8a, 0x00, 1, 2, 3, 4, 5, 6, 7, 8]);
+        doc.extend([0x09]);
+        doc.extend(b"at\0");
+        doc.extend(1_714_557_600_123i64.to_le_bytes());
+        doc.extend([0x04]);
+        doc.extend(b"tags\0");
+        doc.extend([12, 0, 0, 0, 0x10, b'0', 0, 5, 0, 0, 0, 0]);
+        let mut bytes = ((doc.len() + 5) as i32).to_le_bytes().to_vec();
+        bytes.extend(&doc);
+        bytes.push(0);
+        let two = [bytes.clone(), bytes].concat();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&two).unwrap();
+        let out = decode_serialized(file.path().to_string_lossy().into_owned(), None).unwrap();
+        assert_eq!((out.format.as_str(), out.documents), ("bson", 2));
+        assert!(out.json.starts_with("[\n  {\n    \"_id\": {\n      \"$type\": \"objectid\",\n      \"value\": \"66318a000102030405060708\"\n    },"));
+        assert!(out.json.contains("\"value\": \"2024-05-01T10:00:00.123Z\""));
+        assert!(out.json.contains("\"tags\": [\n      5\n    ]"));
+    }
+}
@@ -0,0 +1,134 @@
+//! Excel (.xlsx, .xlsm, .xlsb, .xls) and OpenDocument (.ods) workbooks, each sheet exposed as CSV
+//! with the type of every cell kept alongside for display.
+
+use calamine::{open_workbook_auto, Data, Range, Reader};
+use serde::Serialize;
+
+use crate::align_csv;
+
+/// One sheet of a workbook.
+#[derive(Debug, Serialize)]
+pub struct Sheet {
+    pub name: String,
+    /// 1-based row and column of the first cell of the used range (`A1` is 1, 1).
+    pub start_row: u32,
+    pub start_column: u32,
+    /// The cells as CSV, one record per row, for opening in a CSV tab.
+    pub csv: String,
+    /// The same cells column-aligned, as `format_csv` renders them.
+    pub aligned: String,
+    /// Type of each cell, row by row: `string`, `int`, `float`, `bool`, `datetime`, `duration`,
+    /// `error` or `empty`.
+    pub types: Vec<Vec<&'static str>>,
+}
+
+/// Cell text as a spreadsheet would show it (dates in ISO 8601, booleans as `TRUE`/`FALSE`) and
+/// the cell's type.
+fn cell(data: &Data) -> (String, &'static str) {
+    match data {
+        Data::Empty => (String::new(), "empty"),
+        Data::String(s) => (s.clone(), "string"),
+        Data::Int(i) => (i.to_string(), "int"),
+        Data::Float(f) => (f.to_string(), "float"),
+        Data::Bool(b) => ((if *b { "TRUE" } else { "FALSE" }).to_string(), "bool"),
+        Data::Error(e) => (e.to_string(), "error"),
+        Data::DateTimeIso(s) => (s.clone(), "datetime"),
+        Data::DurationIso(s) => (s.clone(), "duration"),
+        Data::DateTime(dt) if dt.is_duration() => {
+            let secs = (dt.as_f64() * 86_400.0).round() as i64;
+            let text = format!("{}:{:02}:{:02}", secs / 3600, secs % 3600 / 60, secs % 60);
+            (text, "duration")
+        }
+        Data::DateTime(dt) => match dt.as_datetime() {
+            Some(d) if d.time() == chrono::NaiveTime::MIN => {
+                (d.format("%Y-%m-%d").to_string(), "datetime")
+            }
+            Some(d) => (d.format("%Y-%m-%d %H:%M:%S").to_string(), "datetime"),
+            None => (dt.as_f64().to_string(), "float"),
+        },
+    }
+}
+
+fn sheet(name: String, range: &Range<Data>) -> Result<Sheet, String> {
+    let (row, column) = range.start().unwrap_or((0, 0));
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let mut types = Vec::with_capacity(range.height());
+    for cells in range.rows() {
+        let (texts, row_types): (Vec<String>, Vec<&'static str>) = cells.iter().map(cell).unzip();
+        writer.write_record(&texts).map_err(|e| e.to_string())?;
+        types.push(row_types);
+    }
+    let bytes = writer.into_inner().map_err(|e| e.to_string())?;
+    let csv = String::from_utf8_lossy(&bytes).into_owned();
+    Ok(Sheet {
+        name,
+        start_row: row + 1,
+        start_column: column + 1,
+        aligned: align_csv(&csv)?,
+        csv,
+        types,
+    })
+}
+
+/// Read every sheet of a workbook.
+#[tauri::command]
+pub fn spreadsheet_sheets(path: String) -> Result<Vec<Sheet>, String> {
+    let mut workbook = open_workbook_auto(&path).map_err(|e| e.to_string())?;
+    workbook
+        .sheet_names()
+        .into_iter()
+        .map(|name| {
+            let range = workbook.worksheet_range(&name).map_err(|e| e.to_string())?;
+            sheet(name, &range)
+        })
+        .collect()
+}
+
+/// One sheet of a workbook as CSV, for export.
+#[tauri::command]
+pub fn spreadsheet_export_csv(path: String, sheet_name: String) -> Result<String, String> {
+    let mut workbook = open_workbook_auto(&path).map_err(|e| e.to_string())?;
+    let range = workbook
+        .worksheet_range(&sheet_name)
+        .map_err(|e| e.to_string())?;
+    Ok(sheet(sheet_name, &range)?.csv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> String {
+        concat!(env!("CARGO_MANIFEST_DIR"), "/../test-files/sample.xlsx").to_string()
+    }
+
+    #[test]
+    fn spreadsheet_sheets_reads_each_sheet_with_cell_types() {
+        let sheets = spreadsheet_sheets(sample()).unwrap();
+        let names: Vec<&str> = sheets.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["Orders", "Notes"]);
+        let orders = &sheets[0];
+        assert_eq!(
+            orders.csv,
+            "id,customer,amount,paid,ordered_on\n\
+             1,Alice,19.5,TRUE,2024-05-01\n\
+             2,\"Bob, Inc.\",120,FALSE,2024-05-02 12:00:00\n\
+             3,Carol,#DIV/0!,FALSE,\n"
+        );
+        assert_eq!(
+            orders.types[1],
+            vec!["float", "string", "float", "bool", "datetime"]
+        );
+        assert_eq!(orders.types[3][2..], ["error", "bool", "empty"]);
+        assert!(orders
+            .aligned
+            .starts_with("id  customer   amount   paid   ordered_on"));
+    }
+
+    #[test]
+    fn spreadsheet_export_csv_reads_one_sheet() {
+        let csv = spreadsheet_export_csv(sample(), "Notes".to_string()).unwrap();
+        assert_eq!(csv, "note\nQ2 numbers are preliminary\n");
+        assert!(spreadsheet_export_csv(sample(), "Missing".to_string()).is_err());
+    }
+}
@@ -0,0 +1,809 @@
+//! SQL: detection, statement splitting and a dialect-tolerant formatter.
+//!
+//! The tokenizer understands enough of the common dialects (standard strings and quoted
+//! identifiers, MySQL backticks, T-SQL brackets, Postgres dollar quoting and `::` casts) to never
+//! split or re-case anything inside a literal; everything else is treated generically.
+
+use crate::Segment;
+
+/// Words that are upper-cased by the formatter.
+const KEYWORDS: &[&str] = &[
+    "ADD",
+    "ALL",
+    "ALTER",
+    "AND",
+    "ANY",
+    "AS",
+    "ASC",
+    "BEGIN",
+    "BETWEEN",
+    "BY",
+    "CASCADE",
+    "CASE",
+    "CAST",
+    "CHECK",
+    "COALESCE",
+    "COLUMN",
+    "COMMIT",
+    "CONFLICT",
+    "CONSTRAINT",
+    "COUNT",
+    "CREATE",
+    "CROSS",
+    "DEFAULT",
+    "DELETE",
+    "DESC",
+    "DISTINCT",
+    "DO",
+    "DROP",
+    "ELSE",
+    "END",
+    "ESCAPE",
+    "EXCEPT",
+    "EXISTS",
+    "EXPLAIN",
+    "FALSE",
+    "FETCH",
+    "FILTER",
+    "FIRST",
+    "FOR",
+    "FOREIGN",
+    "FROM",
+    "FULL",
+    "FUNCTION",
+    "GRANT",
+    "GROUP",
+    "HAVING",
+    "IF",
+    "ILIKE",
+    "IN",
+    "INDEX",
+    "INNER",
+    "INSERT",
+    "INTERSECT",
+    "INTERVAL",
+    "INTO",
+    "IS",
+    "JOIN",
+    "KEY",
+    "LANGUAGE",
+    "LAST",
+    "LATERAL",
+    "LEFT",
+    "LIKE",
+    "LIMIT",
+    "MATCHED",
+    "MAX",
+    "MERGE",
+    "MIN",
+    "NATURAL",
+    "NEXT",
+    "NOT",
+    "NOTHING",
+    "NULL",
+    "NULLS",
+    "OFFSET",
+    "ON",
+    "ONLY",
+    "OR",
+    "ORDER",
+    "OUTER",
+    "OVER",
+    "PARTITION",
+    "PRIMARY",
+    "RECURSIVE",
+    "REFERENCES",
+    "REPLACE",
+    "RETURNING",
+    "RETURNS",
+    "REVOKE",
+    "RIGHT",
+    "ROLLBACK",
+    "ROWS",
+    "SELECT",
+    "SET",
+    "SUM",
+    "AVG",
+    "TABLE",
+    "THEN",
+    "TOP",
+    "TRIGGER",
+    "TRUE",
+    "TRUNCATE",
+    "UNION",
+    "UNIQUE",
+    "UPDATE",
+    "USING",
+    "VALUES",
+    "VIEW",
+    "WHEN",
+    "WHERE",
+    "WINDOW",
+    "WITH",
+];
+
+/// Words a statement can start with.
+const STATEMENT_STARTERS: &[&str] = &[
+    "ALTER", "CREATE", "DELETE", "DROP", "EXPLAIN", "GRANT", "INSERT", "MERGE", "REPLACE",
+    "REVOKE", "SELECT", "TRUNCATE", "UPDATE", "WITH",
+];
+
+/// Keywords that, at the start of a line, continue the statement on the line before.
+const CONTINUATION_KEYWORDS: &[&str] = &[
+    "AND",
+    "AS",
+    "CROSS",
+    "ELSE",
+    "END",
+    "EXCEPT",
+    "FROM",
+    "FULL",
+    "GROUP",
+    "HAVING",
+    "INNER",
+    "INTERSECT",
+    "INTO",
+    "JOIN",
+    "LEFT",
+    "LIMIT",
+    "OFFSET",
+    "ON",
+    "OR",
+    "ORDER",
+    "OUTER",
+    "RETURNING",
+    "RIGHT",
+    "SELECT",
+    "SET",
+    "THEN",
+    "UNION",
+    "VALUES",
+    "WHEN",
+    "WHERE",
+];
+
+/// Words that end a SELECT list or SET list when looking ahead for commas.
+const LIST_TERMINATORS: &[&str] = &[
+    "EXCEPT",
+    "FETCH",
+    "FROM",
+    "GROUP",
+    "HAVING",
+    "INTERSECT",
+    "INTO",
+    "LIMIT",
+    "OFFSET",
+    "ORDER",
+    "RETURNING",
+    "UNION",
+    "WHERE",
+    "WINDOW",
+];
+
+/// Words that can precede JOIN in a join operator (`LEFT OUTER JOIN`).
+const JOIN_PREFIXES: &[&str] = &[
+    "CROSS", "FULL", "INNER", "LEFT", "NATURAL", "OUTER", "RIGHT",
+];
+
+/// Keywords followed by a space before `(`; after any other word, `(` is a function call.
+const SPACE_BEFORE_PAREN: &[&str] = &[
+    "AND", "AS", "EXISTS", "FROM", "IN", "JOIN", "NOT", "ON", "OR", "OVER", "SELECT", "USING",
+    "VALUES", "WHERE",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Word,
+    /// String literals, quoted identifiers and dollar-quoted bodies.
+    Quoted,
+    Number,
+    Operator,
+    Open,
+    Close,
+    Comma,
+    Semicolon,
+    Dot,
+    LineComment,
+    BlockComment,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    kind: Kind,
+    text: &'a str,
+    start: usize,
+}
+
+/// End of a quoted run starting at `start` with delimiter `close`; a doubled delimiter is an escape.
+fn quoted_end(s: &str, start: usize, close: u8) -> usize {
+    let b = s.as_bytes();
+    let mut i = start + 1;
+    while i < b.len() {
+        if b[i] == close {
+            if b.get(i + 1) == Some(&close) && close != b']' {
+                i += 2;
+                continue;
+            }
+            return i + 1;
+        }
+        i += 1;
+    }
+    s.len()
+}
+
+/// End of a Postgres dollar-quoted string (`$tag$ ... $tag$`) starting at `start`, if it is one.
+fn dollar_quoted_end(s: &str, start: usize) -> Option<usize> {
+    let rest = &s[start + 1..];
+    let tag_len = rest.find('$')?;
+    let tag = &rest[..tag_len];
+    if !tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        || tag.starts_with(|c: char| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let delim = &s[start..start + tag_len + 2];
+    let body = start + delim.len();
+    Some(
+        s[body..]
+            .find(delim)
+            .map_or(s.len(), |e| body + e + delim.len()),
+    )
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$' || c == '@' || c == '#'
+}
+
+fn tokenize(s: &str) -> Vec<Token<'_>> {
+    let b = s.as_bytes();
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut i = 0;
+    while i < b.len() {
+        let c = s[i..].chars().next().unwrap();
+        if c.is_whitespace() {
+            i += c.len_utf8();
+            continue;
+        }
+        let prev_is_value = tokens.last().is_some_and(|t| {
+            matches!(
+                t.kind,
+                Kind::Word | Kind::Quoted | Kind::Number | Kind::Close
+            )
+        });
+        let (kind, end) = match c {
+            '-' if b.get(i + 1) == Some(&b'-') => (
+                Kind::LineComment,
+                s[i..].find('\n').map_or(s.len(), |e| i + e),
+            ),
+            '/' if b.get(i + 1) == Some(&b'*') => (
+                Kind::BlockComment,
+                s[i + 2..].find("*/").map_or(s.len(), |e| i + e + 4),
+            ),
+            '\'' => (Kind::Quoted, quoted_end(s, i, b'\'')),
+            '"' => (Kind::Quoted, quoted_end(s, i, b'"')),
+            '`' => (Kind::Quoted, quoted_end(s, i, b'`')),
+            '[' => (Kind::Quoted, quoted_end(s, i, b']')),
+            '$' if dollar_quoted_end(s, i).is_some() => {
+                (Kind::Quoted, dollar_quoted_end(s, i).unwrap())
+            }
+            '(' => (Kind::Open, i + 1),
+            ')' => (Kind::Close, i + 1),
+            ',' => (Kind::Comma, i + 1),
+            ';' => (Kind::Semicolon, i + 1),
+            '.' if !b.get(i + 1).is_some_and(u8::is_ascii_digit) => (Kind::Dot, i + 1),
+            ':' if s[i + 1..].starts_with(|c: char| c.is_alphabetic() || c == '_') => {
+                // Named placeholder (`:name`).
+                let len = s[i + 1..]
+                    .find(|c: char| !is_word_char(c))
+                    .unwrap_or(s.len() - i - 1);
+                (Kind::Word, i + 1 + len)
+            }
+            c if c.is_ascii_digit()
+                || (c == '.' || (c == '-' && !prev_is_value))
+                    && b.get(i + 1).is_some_and(u8::is_ascii_digit) =>
+            {
+                let len = s[i + 1..]
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.'))
+                    .unwrap_or(s.len() - i - 1);
+                (Kind::Number, i + 1 + len)
+            }
+            c if is_word_char(c) || c == '?' => {
+                let len = s[i..]
+                    .find(|c: char| !is_word_char(c))
+                    .unwrap_or(s.len() - i);
+                (Kind::Word, i + len.max(1))
+            }
+            _ => {
+                let len = s[i..]
+                    .find(|c: char| !"<>=!|+-*/%^~&:".contains(c))
+                    .unwrap_or(s.len() - i);
+                (Kind::Operator, i + len.max(c.len_utf8()))
+            }
+        };
+        tokens.push(Token {
+            kind,
+            text: &s[i..end],
+            start: i,
+        });
+        i = end;
+    }
+    tokens
+}
+
+fn upper(t: &Token) -> String {
+    if t.kind == Kind::Word {
+        t.text.to_ascii_uppercase()
+    } else {
+        String::new()
+    }
+}
+
+/// True if the tokens hold more than comments and semicolons.
+fn has_code(tokens: &[Token]) -> bool {
+    tokens.iter().any(|t| {
+        !matches!(
+            t.kind,
+            Kind::LineComment | Kind::BlockComment | Kind::Semicolon
+        )
+    })
+}
+
+/// Tokens split into statements at top-level semicolons (each statement keeps its semicolon).
+fn statements<'a>(tokens: &'a [Token<'a>]) -> Vec<&'a [Token<'a>]> {
+    tokens
+        .split_inclusive(|t| t.kind == Kind::Semicolon)
+        .filter(|s| has_code(s))
+        .collect()
+}
+
+/// True if the statement opens with a statement keyword followed by one that confirms it is SQL
+/// (`SELECT … FROM`, `INSERT INTO`, `UPDATE … SET`, `CREATE TABLE`, ...).
+fn is_statement(tokens: &[Token]) -> bool {
+    let words: Vec<String> = tokens
+        .iter()
+        .filter(|t| !matches!(t.kind, Kind::LineComment | Kind::BlockComment))
+        .map(upper)
+        .collect();
+    let Some(first) = words.first() else {
+        return false;
+    };
+    let has = |w: &str| words.iter().skip(1).any(|x| x == w);
+    match first.as_str() {
+        // Without FROM, only `SELECT` written in capitals or a terminated statement (`select 1;`) is
+        // taken as SQL, so a sentence starting with "Select" is not.
+        "SELECT" => {
+            has("FROM")
+                || tokens.last().is_some_and(|t| t.kind == Kind::Semicolon)
+                || tokens.iter().any(|t| t.text == "SELECT")
+        }
+        "INSERT" | "MERGE" | "REPLACE" => has("INTO"),
+        "UPDATE" => has("SET"),
+        "DELETE" => has("FROM"),
+        "WITH" => has("AS") && (has("SELECT") || has("INSERT") || has("UPDATE") || has("DELETE")),
+        "CREATE" | "ALTER" | "DROP" => words.get(1).is_some_and(|w| {
+            matches!(
+                w.as_str(),
+                "TABLE"
+                    | "VIEW"
+                    | "INDEX"
+                    | "UNIQUE"
+                    | "SCHEMA"
+                    | "DATABASE"
+                    | "FUNCTION"
+                    | "PROCEDURE"
+                    | "TRIGGER"
+                    | "SEQUENCE"
+                    | "TYPE"
+                    | "EXTENSION"
+                    | "OR"
+                    | "MATERIALIZED"
+                    | "TEMP"
+                    | "TEMPORARY"
+                    | "USER"
+                    | "ROLE"
+            )
+        }),
+        "TRUNCATE" => true,
+        "GRANT" | "REVOKE" => has("ON"),
+        "EXPLAIN" => words
+            .iter()
+            .skip(1)
+            .any(|w| STATEMENT_STARTERS.contains(&w.as_str())),
+        _ => false,
+    }
+}
+
+/// True if the text starts with an SQL statement.
+pub fn looks_like_sql(content: &str) -> bool {
+    let tokens = tokenize(content);
+    statements(&tokens).first().is_some_and(|s| is_statement(s))
+}
+
+/// True if the whole text is a sequence of SQL statements (a `.sql` file or a pasted script).
+pub fn is_sql_script(content: &str) -> bool {
+    let tokens = tokenize(content);
+    let stmts = statements(&tokens);
+    !stmts.is_empty() && stmts.iter().all(|s| is_statement(s))
+}
+
+/// True if `line` carries on an SQL statement from `prev` (the previous line has no terminating
+/// `;` and this one is indented, starts with a clause keyword, or follows a trailing comma).
+pub fn continues_statement(prev: &str, line: &str) -> bool {
+    let p = prev.trim_end();
+    if p.ends_with(';') {
+        return false;
+    }
+    let t = line.trim_start();
+    let first = t
+        .split(|c: char| !is_word_char(c))
+        .next()
+        .unwrap_or("")
+        .to_ascii_uppercase();
+    line.len() != t.len()
+        || t.starts_with([')', ',', '('])
+        || p.ends_with([',', '('])
+        || CONTINUATION_KEYWORDS.contains(&first.as_str())
+}
+
+/// One segment per statement (comments above a statement belong to it, as do comments after the
+/// last one). Statements sharing a line share a segment; a file of only comments is one segment.
+pub fn statement_segments(content: &str) -> Vec<Segment> {
+    let tokens = tokenize(content);
+    let line_of = |offset: usize| {
+        content.as_bytes()[..offset]
+            .iter()
+            .filter(|&&b| b == b'\n')
+            .count() as u32
+            + 1
+    };
+    let mut segments: Vec<Segment> = Vec::new();
+    for chunk in tokens.split_inclusive(|t| t.kind == Kind::Semicolon) {
+        let first = chunk.first().unwrap();
+        let last = chunk.last().unwrap();
+        let (start, end) = (
+            line_of(first.start),
+            line_of(last.start + last.text.len().saturating_sub(1)),
+        );
+        match segments.last_mut() {
+            Some(prev) if prev.end_line >= start || !has_code(chunk) => prev.end_line = end,
+            _ => segments.push(Segment {
+                start_line: start,
+                end_line: end,
+                kind: "sql".to_string(),
+                label: None,
+                dialect: None,
+            }),
+        }
+    }
+    segments
+}
+
+/// Formatting state for one level of parentheses (or the statement itself).
+#[derive(Debug, Clone, Copy)]
+struct Context {
+    /// Top level or a parenthesized subquery: clause keywords start new lines here.
+    clauses: bool,
+    /// Indent level of clause keywords in this context.
+    base: usize,
+    /// Put each item of the current SELECT / SET list on its own line.
+    list_break: bool,
+    /// The first list item still needs its line break (after `SELECT` / `DISTINCT`).
+    pending_item: bool,
+    /// Inside `BETWEEN x AND y`: the next AND is not a condition separator.
+    between: bool,
+}
+
+impl Context {
+    fn new(clauses: bool, base: usize) -> Self {
+        Context {
+            clauses,
+            base,
+            list_break: false,
+            pending_item: false,
+            between: false,
+        }
+    }
+}
+
+struct Printer<'a> {
+    out: String,
+    at_line_start: bool,
+    level: usize,
+    /// One level of indentation.
+    unit: &'a str,
+}
+
+impl Printer<'_> {
+    fn newline(&mut self, level: usize) {
+        self.out.truncate(self.out.trim_end_matches(' ').len());
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+        self.out.push_str(&self.unit.repeat(level));
+        self.at_line_start = true;
+        self.level = level;
+    }
+
+    fn emit(&mut self, text: &str, space: bool) {
+        if space && !self.at_line_start {
+            self.out.push(' ');
+        }
+        self.out.push_str(text);
+        self.at_line_start = false;
+    }
+}
+
+/// True if a clause keyword should start a new line, given its neighbours.
+fn breaks_before(word: &str, prev: &str, next: &str, statement_start: bool) -> bool {
+    match word {
+        "SELECT" | "WHERE" | "HAVING" | "LIMIT" | "OFFSET" | "RETURNING" | "VALUES" | "WINDOW"
+        | "UNION" | "INTERSECT" | "EXCEPT" | "INSERT" | "FETCH" => true,
+        "FROM" => !matches!(prev, "DELETE" | "DISTINCT"),
+        "GROUP" | "ORDER" => next == "BY",
+        "JOIN" => !JOIN_PREFIXES.contains(&prev),
+        "LEFT" | "RIGHT" | "INNER" | "FULL" | "CROSS" | "NATURAL" => {
+            matches!(next, "JOIN" | "OUTER" | "INNER" | "LEFT" | "RIGHT")
+                && !JOIN_PREFIXES.contains(&prev)
+        }
+        "UPDATE" | "DELETE" => !matches!(prev, "ON" | "FOR"),
+        "SET" => !matches!(prev, "CHARACTER" | "UPDATE" | "DELETE" | "DO"),
+        "WITH" | "CREATE" | "ALTER" | "DROP" | "MERGE" | "EXPLAIN" => statement_start,
+        _ => false,
+    }
+}
+
+/// True if the list that follows (a SELECT list or SET assignments) has more than one item.
+fn has_top_level_comma(tokens: &[Token]) -> bool {
+    let mut depth = 0usize;
+    for t in tokens {
+        match t.kind {
+            Kind::Open => depth += 1,
+            Kind::Close if depth == 0 => return false,
+            Kind::Close => depth -= 1,
+            Kind::Semicolon => return false,
+            Kind::Comma if depth == 0 => return true,
+            Kind::Word if depth == 0 && LIST_TERMINATORS.contains(&upper(t).as_str()) => {
+                return false
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Whether to put a space between two adjacent tokens.
+fn needs_space(prev: Option<(Kind, &str)>, prev2: &str, cur: &Token) -> bool {
+    let Some((prev_kind, prev_text)) = prev else {
+        return false;
+    };
+    if matches!(
+        cur.kind,
+        Kind::Comma | Kind::Semicolon | Kind::Close | Kind::Dot
+    ) || matches!(prev_kind, Kind::Open | Kind::Dot)
+        || prev_text == "::"
+        || cur.text == "::"
+    {
+        return false;
+    }
+    if cur.kind == Kind::Open && prev_kind == Kind::Word {
+        let word = prev_text.to_ascii_uppercase();
+        return SPACE_BEFORE_PAREN.contains(&word.as_str()) || prev2 == "INTO";
+    }
+    true
+}
+
+/// Pretty-print SQL: upper-case keywords, each clause on its own line, one item per line in
+/// multi-column SELECT / SET lists, AND / OR conditions indented under their clause, and
+/// subqueries indented inside their parentheses, each level by `indent`. Literals and comments
+/// are kept as written.
+pub fn format_sql(content: &str, indent: &str) -> String {
+    let tokens = tokenize(content);
+    let mut p = Printer {
+        out: String::new(),
+        at_line_start: true,
+        level: 0,
+        unit: indent,
+    };
+    let mut stack = vec![Context::new(true, 0)];
+    let mut prev: Option<(Kind, String)> = None;
+    let mut prev2 = String::new();
+    let mut statement_start = true;
+    for (i, t) in tokens.iter().enumerate() {
+        let next = tokens[i + 1..]
+            .iter()
+            .find(|n| !matches!(n.kind, Kind::LineComment | Kind::BlockComment));
+        let next_upper = next.map(upper).unwrap_or_default();
+        let prev_upper = prev
+            .as_ref()
+            .map(|(_, s)| s.to_ascii_uppercase())
+            .unwrap_or_default();
+        let word = upper(t);
+        let is_keyword = t.kind == Kind::Word
+            && KEYWORDS.contains(&word.as_str())
+            && !matches!(prev.as_ref().map(|(k, _)| *k), Some(Kind::Dot))
+            && next.is_none_or(|n| n.kind != Kind::Dot);
+        let ctx = stack.last_mut().unwrap();
+
+        if is_keyword && ctx.clauses {
+            if breaks_before(&word, &prev_upper, &next_upper, statement_start) {
+                ctx.list_break = false;
+                ctx.pending_item = false;
+                let base = ctx.base;
+                p.newline(base);
+            } else if matches!(word.as_str(), "AND" | "OR") {
+                if ctx.between {
+                    ctx.between = false;
+                } else {
+                    let level = ctx.base + 1;
+                    p.newline(level);
+                }
+            }
+            if word == "BETWEEN" {
+                ctx.between = true;
+            }
+        }
+        let is_modifier = is_keyword && matches!(word.as_str(), "DISTINCT" | "ALL");
+        if ctx.pending_item
+            && !is_modifier
+            && !matches!(t.kind, Kind::LineComment | Kind::BlockComment)
+        {
+            ctx.pending_item = false;
+            let level = ctx.base + 1;
+            p.newline(level);
+        }
+
+        let prev_ref = prev.as_ref().map(|(k, s)| (*k, s.as_str()));
+        let space = needs_space(prev_ref, &prev2, t);
+        let text = if is_keyword {
+            word.clone()
+        } else {
+            t.text.to_string()
+        };
+        match t.kind {
+            Kind::Open => {
+                let subquery = next_upper == "SELECT" || next_upper == "WITH";
+                let base = if subquery { ctx.base + 1 } else { ctx.base };
+                p.emit("(", space);
+                stack.push(Context::new(subquery, base));
+            }
+            Kind::Close => {
+                if stack.len() > 1 {
+                    let closed = stack.pop().unwrap();
+                    if closed.clauses {
+                        p.newline(closed.base - 1);
+                    }
+                }
+                p.emit(")", space);
+            }
+            Kind::Comma => {
+                p.emit(",", space);
+                if ctx.clauses && ctx.list_break {
+                    let level = ctx.base + 1;
+                    p.newline(level);
+                }
+            }
+            Kind::Semicolon => {
+                p.emit(";", space);
+                p.out.push_str("\n\n");
+                p.at_line_start = true;
+                stack.truncate(1);
+                stack[0] = Context::new(true, 0);
+            }
+            Kind::LineComment => {
+                p.emit(t.text.trim_end(), space);
+                let level = p.level;
+                p.newline(level);
+            }
+            _ => {
+                p.emit(&text, space);
+                if is_keyword && ctx.clauses && matches!(word.as_str(), "SELECT" | "SET") {
+                    let multi = has_top_level_comma(&tokens[i + 1..]);
+                    ctx.list_break = multi;
+                    ctx.pending_item = multi;
+                }
+            }
+        }
+        if !matches!(t.kind, Kind::LineComment | Kind::BlockComment) {
+            statement_start = t.kind == Kind::Semicolon;
+            prev2 = prev_upper;
+            prev = Some((t.kind, text));
+        }
+    }
+    p.out.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn looks_like_sql_needs_a_statement() {
+        assert!(looks_like_sql("select * from t"));
+        assert!(looks_like_sql("UPDATE users SET name = 'x' WHERE id = 1"));
+        assert!(looks_like_sql("-- note\nCREATE TABLE t (id int)"));
+        assert!(!looks_like_sql("select the option you prefer and continue"));
+        assert!(!looks_like_sql("update: deployed the fix"));
+        assert!(!looks_like_sql("Select a file"));
+        assert!(looks_like_sql("select 1;"));
+        assert!(looks_like_sql("SELECT a,"));
+    }
+
+    #[test]
+    fn format_sql_breaks_clauses_and_lists() {
+        let out = format_sql("select a, count(*) as n from t left join u on u.id = t.uid where x between 1 and 5 and y = 'and' group by a order by n desc;", "  ");
+        assert_eq!(
+            out,
+            "SELECT\n  a,\n  COUNT(*) AS n\nFROM t\nLEFT JOIN u ON u.id = t.uid\nWHERE x BETWEEN 1 AND 5\n  AND y = 'and'\nGROUP BY a\nORDER BY n DESC;"
+        );
+    }
+
+    #[test]
+    fn format_sql_indents_subqueries() {
+        let out = format_sql("SELECT * FROM (select id from users where active) u where u.id in (select user_id from orders)", "  ");
+        assert_eq!(
+            out,
+            "SELECT *\nFROM (\n  SELECT id\n  FROM users\n  WHERE active\n) u\nWHERE u.id IN (\n  SELECT user_id\n  FROM orders\n)"
+        );
+    }
+
+    #[test]
+    fn format_sql_keeps_literals_and_dialect_syntax() {
+        let out = format_sql(
+            "insert into `Order` (id, \"From\") values ($1, 'select from'), (:id, -2)",
+            "  ",
+        );
+        assert_eq!(
+            out,
+            "INSERT INTO `Order` (id, \"From\")\nVALUES ($1, 'select from'), (:id, -2)"
+        );
+        assert_eq!(
+            format_sql("select x::int, t.order from t", "  "),
+            "SELECT\n  x::int,\n  t.order\nFROM t"
+        );
+        assert_eq!(
+            format_sql("select $$ a ; b $$ -- trailing\nfrom t", "  "),
+            "SELECT $$ a ; b $$ -- trailing\nFROM t"
+        );
+    }
+
+    #[test]
+    fn statement_segments_split_on_semicolons() {
+        let content = "-- first\nselect 1;\n\nupdate t\nset a = ';'\nwhere b;\nselect 2; select 3;";
+        let segs: Vec<(u32, u32)> = statement_segments(content)
+            .iter()
+            .map(|s| (s.start_line, s.end_line))
+            .collect();
+        assert_eq!(segs, vec![(1, 2), (4, 6), (7, 7)]);
+        assert!(is_sql_script(content));
+        assert!(!is_sql_script("select 1;\nhello there"));
+        let segs: Vec<(u32, u32)> = statement_segments("select id from users;\nselect * from café")
+            .iter()
+            .map(|s| (s.start_line, s.end_line))
+            .collect();
+        assert_eq!(segs, vec![(1, 1), (2, 2)]);
+    }
+
+    #[test]
+    fn statement_segments_keep_trailing_and_lone_comments() {
+        let segs: Vec<(u32, u32)> = statement_segments("select 1;\n-- done\n/* end */")
+            .iter()
+            .map(|s| (s.start_line, s.end_line))
+            .collect();
+        assert_eq!(segs, vec![(1, 3)]);
+        let segs = statement_segments("-- nothing yet\n\n-- todo");
+        assert_eq!(
+            (segs.len(), segs[0].start_line, segs[0].end_line),
+            (1, 1, 3)
+        );
+        assert_eq!(segs[0].kind, "sql");
+    }
+
+    #[test]
+    fn continues_statement_follows_clauses() {
+        assert!(continues_statement("SELECT a,", "b"));
+        assert!(continues_statement("SELECT a", "FROM t"));
+        assert!(continues_statement("SELECT a", "    , b"));
+        assert!(!continues_statement("SELECT a;", "FROM t"));
+        assert!(!continues_statement("SELECT a FROM t", "done."));
+    }
+}
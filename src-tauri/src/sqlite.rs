@@ -0,0 +1,289 @@
+//! SQLite browser: tables and views with their schema, paged table rows and read-only queries,
+//! returned as CSV plus a column-aligned rendering for display.
+
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+
+use crate::align_csv;
+
+/// Rows returned per page when no limit is given.
+const PAGE_SIZE: u64 = 200;
+
+#[derive(Debug, Serialize)]
+pub struct SqliteColumn {
+    pub name: String,
+    /// Declared type, empty when the column has none.
+    pub decl_type: String,
+    pub not_null: bool,
+    pub primary_key: bool,
+}
+
+/// A table or view of the database.
+#[derive(Debug, Serialize)]
+pub struct SqliteObject {
+    pub name: String,
+    /// `table` or `view`.
+    pub kind: String,
+    /// The `CREATE` statement.
+    pub sql: String,
+    pub columns: Vec<SqliteColumn>,
+    /// Number of rows, for tables.
+    pub rows: Option<u64>,
+}
+
+/// A page of rows from a table or query.
+#[derive(Debug, Serialize)]
+pub struct SqliteRows {
+    pub columns: Vec<String>,
+    /// The rows as CSV with a header row, for opening in a CSV tab.
+    pub csv: String,
+    /// The same rows column-aligned, as `format_csv` renders them.
+    pub aligned: String,
+    pub offset: u64,
+    pub row_count: usize,
+    /// True if there are rows after this page.
+    pub has_more: bool,
+}
+
+fn open(path: &str) -> Result<Connection, String> {
+    Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .map_err(|e| e.to_string())
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn cell_text(value: ValueRef) -> String {
+    match value {
+        ValueRef::Null => "NULL".to_string(),
+        ValueRef::Integer(i) => i.to_string(),
+        ValueRef::Real(f) => f.to_string(),
+        ValueRef::Text(t) => String::from_utf8_lossy(t).into_owned(),
+        ValueRef::Blob(b) => format!("<blob {} bytes>", b.len()),
+    }
+}
+
+/// Run a statement and collect up to `limit` rows starting at `offset` (the statement itself is
+/// given `LIMIT`/`OFFSET` by the caller when it can be).
+fn collect_rows(
+    conn: &Connection,
+    sql: &str,
+    skip: u64,
+    offset: u64,
+    limit: u64,
+) -> Result<SqliteRows, String> {
+    let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+    if !stmt.readonly() {
+        return Err("Only read-only statements can be run.".to_string());
+    }
+    let columns: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(&columns).map_err(|e| e.to_string())?;
+    let mut row_count = 0;
+    let mut skipped = 0;
+    let mut has_more = false;
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        if skipped < skip {
+            skipped += 1;
+            continue;
+        }
+        if row_count as u64 == limit {
+            has_more = true;
+            break;
+        }
+        let record: Vec<String> = (0..columns.len())
+            .map(|i| row.get_ref(i).map(cell_text).map_err(|e| e.to_string()))
+            .collect::<Result<_, _>>()?;
+        writer.write_record(&record).map_err(|e| e.to_string())?;
+        row_count += 1;
+    }
+    let bytes = writer.into_inner().map_err(|e| e.to_string())?;
+    let csv = String::from_utf8_lossy(&bytes).into_owned();
+    let aligned = align_csv(&csv)?;
+    Ok(SqliteRows {
+        columns,
+        csv,
+        aligned,
+        offset,
+        row_count,
+        has_more,
+    })
+}
+
+/// List the tables and views of a database with their columns and (for tables) row counts.
+#[tauri::command]
+pub fn sqlite_schema(path: String) -> Result<Vec<SqliteObject>, String> {
+    let conn = open(&path)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT type, name, coalesce(sql, '') FROM sqlite_master \
+             WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite\\_%' ESCAPE '\\' ORDER BY name",
+        )
+        .map_err(|e| e.to_string())?;
+    let objects = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| e.to_string())?;
+    objects
+        .into_iter()
+        .map(|(kind, name, sql)| {
+            let mut info = conn
+                .prepare(&format!("PRAGMA table_info({})", quote_identifier(&name)))
+                .map_err(|e| e.to_string())?;
+            let columns = info
+                .query_map([], |row| {
+                    Ok(SqliteColumn {
+                        name: row.get(1)?,
+                        decl_type: row.get(2)?,
+                        not_null: row.get(3)?,
+                        primary_key: row.get::<_, i64>(5)? > 0,
+                    })
+                })
+                .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+                .map_err(|e| e.to_string())?;
+            let rows = (kind == "table")
+                .then(|| {
+                    conn.query_row(
+                        &format!("SELECT count(*) FROM {}", quote_identifier(&name)),
+                        [],
+                        |row| row.get::<_, i64>(0),
+                    )
+                    .ok()
+                })
+                .flatten()
+                .map(|n| n as u64);
+            Ok(SqliteObject {
+                name,
+                kind,
+                sql,
+                columns,
+                rows,
+            })
+        })
+        .collect()
+}
+
+/// A page of rows of a table or view (`limit` defaults to [`PAGE_SIZE`]).
+#[tauri::command]
+pub fn sqlite_rows(
+    path: String,
+    table: String,
+    offset: Option<u64>,
+    limit: Option<u64>,
+) -> Result<SqliteRows, String> {
+    let conn = open(&path)?;
+    let offset = offset.unwrap_or(0);
+    let limit = limit.unwrap_or(PAGE_SIZE);
+    let sql = format!(
+        "SELECT * FROM {} LIMIT {} OFFSET {}",
+        quote_identifier(&table),
+        limit + 1,
+        offset
+    );
+    collect_rows(&conn, &sql, 0, offset, limit)
+}
+
+/// Run a read-only query (the database is opened read-only and statements that would write are
+/// refused) and return up to `limit` rows starting at `offset`.
+#[tauri::command]
+pub fn sqlite_query(
+    path: String,
+    sql: String,
+    offset: Option<u64>,
+    limit: Option<u64>,
+) -> Result<SqliteRows, String> {
+    let conn = open(&path)?;
+    let offset = offset.unwrap_or(0);
+    collect_rows(
+        &conn,
+        sql.trim().trim_end_matches(';'),
+        offset,
+        offset,
+        limit.unwrap_or(PAGE_SIZE),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A database in a fresh directory, removed when the returned guard is dropped.
+    fn sample_db() -> (tempfile::TempDir, String) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sample.sqlite");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL, avatar BLOB);
+             CREATE VIEW named AS SELECT name FROM users WHERE name LIKE 'a%';
+             INSERT INTO users (name, avatar) VALUES ('ana', x'0102'), ('bob', NULL), ('amir, jr', NULL);",
+        )
+        .unwrap();
+        (dir, path.to_string_lossy().into_owned())
+    }
+
+    #[test]
+    fn sqlite_schema_lists_tables_and_views() {
+        let (_dir, path) = sample_db();
+        let objects = sqlite_schema(path).unwrap();
+        let names: Vec<(&str, &str, Option<u64>)> = objects
+            .iter()
+            .map(|o| (o.name.as_str(), o.kind.as_str(), o.rows))
+            .collect();
+        assert_eq!(
+            names,
+            vec![("named", "view", None), ("users", "table", Some(3))]
+        );
+        let id = &objects[1].columns[0];
+        assert_eq!(
+            (id.name.as_str(), id.decl_type.as_str(), id.primary_key),
+            ("id", "INTEGER", true)
+        );
+        assert!(objects[1].columns[1].not_null);
+    }
+
+    #[test]
+    fn sqlite_rows_pages_through_a_table() {
+        let (_dir, path) = sample_db();
+        let page = sqlite_rows(path.clone(), "users".to_string(), Some(0), Some(2)).unwrap();
+        assert_eq!(
+            page.csv,
+            "id,name,avatar\n1,ana,<blob 2 bytes>\n2,bob,NULL\n"
+        );
+        assert_eq!(
+            page.aligned,
+            "id  name  avatar        \n1   ana   <blob 2 bytes>\n2   bob   NULL          "
+        );
+        assert!(page.has_more);
+        let page = sqlite_rows(path, "users".to_string(), Some(2), Some(2)).unwrap();
+        assert_eq!((page.row_count, page.has_more), (1, false));
+        assert_eq!(page.csv, "id,name,avatar\n3,\"amir, jr\",NULL\n");
+    }
+
+    #[test]
+    fn sqlite_query_is_read_only() {
+        let (_dir, path) = sample_db();
+        let out = sqlite_query(
+            path.clone(),
+            "select upper(name) as n from named order by n;".to_string(),
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(out.columns, vec!["n"]);
+        assert_eq!(out.csv, "n\n\"AMIR, JR\"\nANA\n");
+        assert!(sqlite_query(path.clone(), "DELETE FROM users".to_string(), None, None).is_err());
+        assert!(sqlite_query(path, "select * from missing".to_string(), None, None).is_err());
+    }
+}
@@ -0,0 +1,676 @@
+//! Stack traces: Java (and other JVM languages), Python, Go, Node and Rust panics/backtraces.
+
+use serde::Serialize;
+
+/// Package prefixes of JVM frames that belong to the runtime or a common framework.
+const JAVA_FRAMEWORK_PREFIXES: &[&str] = &[
+    "java.",
+    "javax.",
+    "jdk.",
+    "sun.",
+    "com.sun.",
+    "jakarta.",
+    "kotlin.",
+    "kotlinx.",
+    "scala.",
+    "org.springframework.",
+    "org.apache.",
+    "org.hibernate.",
+    "org.junit.",
+    "org.eclipse.jetty.",
+    "io.netty.",
+    "reactor.",
+    "akka.",
+    "com.google.common.",
+];
+
+/// Symbol prefixes of Rust frames in the standard library, panic machinery or the async runtime.
+const RUST_FRAMEWORK_PREFIXES: &[&str] = &[
+    "std::",
+    "core::",
+    "alloc::",
+    "tokio::",
+    "<std::",
+    "<core::",
+    "<alloc::",
+    "rust_begin_unwind",
+    "__rust",
+    "__libc",
+    "_start",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Language {
+    Java,
+    Python,
+    Go,
+    Node,
+    Rust,
+}
+
+impl Language {
+    fn name(self) -> &'static str {
+        match self {
+            Language::Java => "java",
+            Language::Python => "python",
+            Language::Go => "go",
+            Language::Node => "node",
+            Language::Rust => "rust",
+        }
+    }
+}
+
+/// One frame of a trace. With framework collapsing, a run of framework frames is reported as its
+/// first frame with `collapsed` set to the length of the run.
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceFrame {
+    pub function: String,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    /// 1-based line of the frame in the buffer.
+    pub source_line: u32,
+    /// Frame in the runtime, standard library, a dependency or a well-known framework.
+    pub framework: bool,
+    /// Number of consecutive framework frames this entry stands for (0 for an ordinary frame).
+    pub collapsed: u32,
+}
+
+/// One exception (or panic / goroutine) in a trace.
+#[derive(Debug, Serialize)]
+pub struct TraceException {
+    /// Exception type: `java.lang.IllegalStateException`, `ValueError`, `panic`, `goroutine 1 [running]`, ...
+    pub kind: String,
+    pub message: String,
+    /// How this exception relates to the one listed before it: `caused by`, `suppressed`,
+    /// `during handling` (Python's implicit chaining) or `goroutine` (another goroutine in a Go dump).
+    pub relation: Option<String>,
+    /// In printed order: the innermost call comes first for Java, Node, Go and Rust, and last for Python.
+    pub frames: Vec<TraceFrame>,
+}
+
+impl TraceException {
+    fn new(kind: &str, message: &str, relation: Option<&str>) -> Self {
+        TraceException {
+            kind: kind.to_string(),
+            message: message.to_string(),
+            relation: relation.map(str::to_string),
+            frames: Vec::new(),
+        }
+    }
+}
+
+/// A stack trace found in the buffer (1-based inclusive lines).
+#[derive(Debug, Serialize)]
+pub struct StackTrace {
+    /// `java`, `python`, `go`, `node` or `rust`.
+    pub language: &'static str,
+    pub start_line: u32,
+    pub end_line: u32,
+    /// Outermost exception first, followed by its causes.
+    pub exceptions: Vec<TraceException>,
+}
+
+/// A trace parsed from the start of a slice of lines, and how many lines it spans.
+struct Parsed {
+    language: Language,
+    len: usize,
+    exceptions: Vec<TraceException>,
+}
+
+fn is_framework(language: Language, function: &str, file: Option<&str>) -> bool {
+    let file = file.unwrap_or("");
+    match language {
+        Language::Java => {
+            // Java 9+ prefixes the module: `java.base/java.lang.Thread.run`.
+            let name = function.rsplit('/').next().unwrap_or(function);
+            JAVA_FRAMEWORK_PREFIXES.iter().any(|p| name.starts_with(p))
+        }
+        Language::Python => {
+            file.contains("site-packages")
+                || file.contains("dist-packages")
+                || file.contains("/lib/python")
+                || file.starts_with("<frozen")
+        }
+        Language::Node => {
+            file.starts_with("node:")
+                || file.starts_with("internal/")
+                || file.contains("node_modules")
+        }
+        Language::Go => {
+            function.starts_with("runtime.")
+                || file.contains("/go/src/")
+                || file.contains("/pkg/mod/")
+        }
+        Language::Rust => {
+            RUST_FRAMEWORK_PREFIXES
+                .iter()
+                .any(|p| function.starts_with(p))
+                || function.contains(" as core::")
+                || file.contains("/rustc/")
+                || file.contains("/.cargo/registry/")
+        }
+    }
+}
+
+fn frame(language: Language, function: &str, loc: Location, index: usize) -> TraceFrame {
+    let (file, line, column) = loc;
+    TraceFrame {
+        framework: is_framework(language, function, file.as_deref()),
+        function: function.to_string(),
+        file,
+        line,
+        column,
+        source_line: index as u32 + 1,
+        collapsed: 0,
+    }
+}
+
+type Location = (Option<String>, Option<u32>, Option<u32>);
+
+/// `file:line[:column]`, split from the right since the file may contain colons (`C:\app`, `node:internal/...`).
+/// Text that is not a location (`Native Method`, `Unknown Source`) gives no file.
+fn location(s: &str) -> Location {
+    let s = s.trim();
+    let file_only = || (!s.is_empty() && !s.contains(' ')).then(|| s.to_string());
+    let Some((rest, last)) = s.rsplit_once(':') else {
+        return (file_only(), None, None);
+    };
+    let Ok(last) = last.parse::<u32>() else {
+        return (file_only(), None, None);
+    };
+    if let Some((file, line)) = rest.rsplit_once(':') {
+        if let Ok(line) = line.parse::<u32>() {
+            return (Some(file.to_string()), Some(line), Some(last));
+        }
+    }
+    (Some(rest.to_string()), Some(last), None)
+}
+
+/// `Type: message` or a bare `Type`, if `Type` is a plain (possibly qualified) identifier.
+fn exception_line(t: &str) -> Option<(&str, &str)> {
+    let (name, message) = t
+        .split_once(": ")
+        .unwrap_or((t.strip_suffix(':').unwrap_or(t), ""));
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '$' | '.'));
+    valid.then_some((name, message.trim()))
+}
+
+fn is_error_name(name: &str) -> bool {
+    let last = name.rsplit('.').next().unwrap_or(name);
+    ["Error", "Exception", "Throwable"]
+        .iter()
+        .any(|s| last.ends_with(s))
+}
+
+fn is_goroutine_header(t: &str) -> bool {
+    t.starts_with("goroutine ") && t.ends_with("]:")
+}
+
+fn parse(lines: &[&str], first: usize) -> Option<Parsed> {
+    let t = lines.first()?.trim();
+    if t == "Traceback (most recent call last):" {
+        Some(parse_python(lines, first))
+    } else if t.starts_with("thread '") && t.contains(" panicked at ") {
+        Some(parse_rust(lines, first))
+    } else if t.starts_with("panic: ") || t.starts_with("fatal error: ") || is_goroutine_header(t) {
+        parse_go(lines, first)
+    } else {
+        parse_java_or_node(lines, first)
+    }
+}
+
+/// Python prints the innermost cause first; chained tracebacks are joined by one of these sentences.
+fn python_chain(t: &str) -> Option<&'static str> {
+    match t {
+        "The above exception was the direct cause of the following exception:" => Some("caused by"),
+        "During handling of the above exception, another exception occurred:" => {
+            Some("during handling")
+        }
+        _ => None,
+    }
+}
+
+/// `File "app.py", line 12, in handler`
+fn python_frame(t: &str, index: usize) -> TraceFrame {
+    let rest = &t["File \"".len()..];
+    let (file, rest) = rest.split_once('"').unwrap_or((rest, ""));
+    let mut line = None;
+    let mut function = "";
+    for part in rest.split(", ") {
+        if let Some(n) = part.strip_prefix("line ") {
+            line = n.trim().parse().ok();
+        } else if let Some(f) = part.strip_prefix("in ") {
+            function = f.trim();
+        }
+    }
+    frame(
+        Language::Python,
+        function,
+        (Some(file.to_string()), line, None),
+        index,
+    )
+}
+
+fn parse_python(lines: &[&str], first: usize) -> Parsed {
+    // As printed: innermost cause first, each later one with the sentence that introduced it.
+    let mut printed = vec![TraceException::new("", "", None)];
+    let mut len = 1;
+    let mut i = 1;
+    while i < lines.len() {
+        let line = lines[i];
+        let t = line.trim();
+        let current = printed.last_mut().unwrap();
+        if !current.kind.is_empty() {
+            // After the exception line: only a chained traceback continues the trace.
+            let next = |from: usize| (from..lines.len()).find(|&j| !lines[j].trim().is_empty());
+            let chained = next(i).and_then(|j| Some((j, python_chain(lines[j].trim())?)));
+            let Some((j, relation)) = chained else {
+                break;
+            };
+            match next(j + 1) {
+                Some(k) if lines[k].trim() == "Traceback (most recent call last):" => {
+                    printed.push(TraceException::new("", "", Some(relation)));
+                    len = k + 1;
+                    i = k + 1;
+                    continue;
+                }
+                _ => break,
+            }
+        }
+        if t.starts_with("File \"") {
+            current.frames.push(python_frame(t, first + i));
+        } else if line.starts_with([' ', '\t']) && !t.is_empty() {
+            // Source excerpt or `^^^^` marker under a frame.
+        } else if let Some((kind, message)) = exception_line(t) {
+            current.kind = kind.to_string();
+            current.message = message.to_string();
+        } else {
+            break;
+        }
+        i += 1;
+        len = i;
+    }
+    // Report outermost first, each exception followed by the one that caused it.
+    let n = printed.len();
+    let relations: Vec<Option<String>> = printed.iter_mut().map(|e| e.relation.take()).collect();
+    printed.reverse();
+    for (j, e) in printed.iter_mut().enumerate().skip(1) {
+        e.relation = relations[n - j].clone();
+    }
+    Parsed {
+        language: Language::Python,
+        len,
+        exceptions: printed,
+    }
+}
+
+/// Rust symbols carry a hash suffix (`::h0123456789abcdef`) that only adds noise.
+fn strip_rust_hash(symbol: &str) -> &str {
+    match symbol.rsplit_once("::h") {
+        Some((name, hash)) if hash.len() == 16 && hash.chars().all(|c| c.is_ascii_hexdigit()) => {
+            name
+        }
+        _ => symbol,
+    }
+}
+
+fn parse_rust(lines: &[&str], first: usize) -> Parsed {
+    let header = lines[0].trim();
+    let after = header.split_once(" panicked at ").map_or("", |(_, a)| a);
+    let mut i = 1;
+    // Before Rust 1.73: `panicked at 'message', src/main.rs:2:5`; since: `panicked at src/main.rs:2:5:`
+    // with the message on the following lines.
+    let (message, at) = if let Some(rest) = after.strip_prefix('\'') {
+        let (message, at) = rest
+            .rsplit_once("', ")
+            .unwrap_or((rest.trim_end_matches('\''), ""));
+        (message.to_string(), at)
+    } else {
+        let mut message: Vec<&str> = Vec::new();
+        while let Some(line) = lines.get(i) {
+            let t = line.trim();
+            if t.is_empty() || t.starts_with("note: ") || t == "stack backtrace:" {
+                break;
+            }
+            message.push(line.trim_end());
+            i += 1;
+        }
+        (message.join("\n"), after.strip_suffix(':').unwrap_or(after))
+    };
+    let mut panic = TraceException::new("panic", &message, None);
+    while let Some(line) = lines.get(i) {
+        let t = line.trim();
+        if t.starts_with("note: ") || t == "stack backtrace:" {
+            // Hints and the backtrace heading.
+        } else if let Some(loc) = t.strip_prefix("at ") {
+            let Some(last) = panic.frames.last_mut() else {
+                break;
+            };
+            (last.file, last.line, last.column) = location(loc);
+            last.framework = is_framework(Language::Rust, &last.function, last.file.as_deref());
+        } else if let Some((_, symbol)) =
+            t.split_once(": ").filter(|(n, _)| n.parse::<u32>().is_ok())
+        {
+            // Older backtraces print the address first: `0: 0x55d4 - std::...`.
+            let symbol = symbol.split_once(" - ").map_or(symbol, |(_, s)| s);
+            panic.frames.push(frame(
+                Language::Rust,
+                strip_rust_hash(symbol.trim()),
+                (None, None, None),
+                first + i,
+            ));
+        } else {
+            break;
+        }
+        i += 1;
+    }
+    if panic.frames.is_empty() {
+        // Without a backtrace the panic location is the only frame.
+        panic
+            .frames
+            .push(frame(Language::Rust, "", location(at), first));
+    }
+    Parsed {
+        language: Language::Rust,
+        len: i,
+        exceptions: vec![panic],
+    }
+}
+
+/// Function part of a Go frame line: `main.(*Server).handle(0xc000010000, {0x1, 0x2})` without its arguments.
+fn go_function(t: &str) -> Option<&str> {
+    if !t.ends_with(')') {
+        return None;
+    }
+    let mut depth = 0;
+    for (i, c) in t.char_indices().rev() {
+        match c {
+            ')' => depth += 1,
+            '(' => {
+                depth -= 1;
+                if depth == 0 {
+                    return (i > 0 && !t[..i].contains(' ')).then(|| &t[..i]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_go(lines: &[&str], first: usize) -> Option<Parsed> {
+    let t0 = lines[0].trim();
+    let mut exceptions = Vec::new();
+    let mut i = if is_goroutine_header(t0) {
+        exceptions.push(TraceException::new(t0.trim_end_matches(':'), "", None));
+        1
+    } else {
+        let (kind, message) = match t0.strip_prefix("panic: ") {
+            Some(message) => ("panic", message),
+            None => (
+                "fatal error",
+                t0.strip_prefix("fatal error: ").unwrap_or(t0),
+            ),
+        };
+        // The panic message is followed (after a blank line or signal details) by the goroutine.
+        let goroutine = (1..lines.len().min(6)).find(|&j| is_goroutine_header(lines[j].trim()))?;
+        exceptions.push(TraceException::new(kind, message, None));
+        goroutine + 1
+    };
+    let mut len = i;
+    while let Some(line) = lines.get(i) {
+        let t = line.trim();
+        let current = exceptions.last_mut().unwrap();
+        if t.is_empty() {
+            if !lines
+                .get(i + 1)
+                .is_some_and(|l| is_goroutine_header(l.trim()))
+            {
+                break;
+            }
+        } else if is_goroutine_header(t) {
+            exceptions.push(TraceException::new(
+                t.trim_end_matches(':'),
+                "",
+                Some("goroutine"),
+            ));
+        } else if line.starts_with([' ', '\t']) {
+            // `\t/app/main.go:12 +0x1d`: location of the frame above.
+            let Some(last) = current.frames.last_mut() else {
+                break;
+            };
+            let loc = t.split_once(" +0x").map_or(t, |(loc, _)| loc);
+            (last.file, last.line, last.column) = location(loc);
+            last.framework = is_framework(Language::Go, &last.function, last.file.as_deref());
+        } else if let Some(creator) = t.strip_prefix("created by ") {
+            let function = creator
+                .split_once(" in goroutine ")
+                .map_or(creator, |(f, _)| f);
+            current
+                .frames
+                .push(frame(Language::Go, function, (None, None, None), first + i));
+        } else if let Some(function) = go_function(t) {
+            current
+                .frames
+                .push(frame(Language::Go, function, (None, None, None), first + i));
+        } else {
+            break;
+        }
+        i += 1;
+        len = i;
+    }
+    Some(Parsed {
+        language: Language::Go,
+        len,
+        exceptions,
+    })
+}
+
+/// `com.example.Service.run(Service.java:42)` (Java) or `handler (/app/index.js:10:5)` / `/app/index.js:10:5` (Node).
+fn jvm_or_node_frame(language: Language, s: &str, index: usize) -> TraceFrame {
+    let split = match language {
+        Language::Java => s.find('(').map(|p| (&s[..p], &s[p + 1..])),
+        _ => s.find(" (").map(|p| (&s[..p], &s[p + 2..])),
+    };
+    match split.filter(|_| s.ends_with(')')) {
+        Some((function, loc)) => frame(language, function, location(&loc[..loc.len() - 1]), index),
+        None => frame(language, "", location(s), index),
+    }
+}
+
+fn parse_java_or_node(lines: &[&str], first: usize) -> Option<Parsed> {
+    let t0 = lines[0].trim();
+    // `Exception in thread "main" java.lang.IllegalStateException: ...`
+    let t0 = t0
+        .strip_prefix("Exception in thread \"")
+        .and_then(|rest| rest.split_once("\" "))
+        .map_or(t0, |(_, rest)| rest);
+    let (kind, message) = exception_line(t0).filter(|(name, _)| is_error_name(name))?;
+    let first_frame = lines.get(1)?.trim().strip_prefix("at ")?;
+    // Java frames are `name(File.java:1)`; Node puts a space before the parenthesis or has none.
+    let java = first_frame.ends_with(')')
+        && first_frame
+            .find('(')
+            .is_some_and(|p| !first_frame[..p].contains(' '));
+    let language = if java { Language::Java } else { Language::Node };
+    let mut exceptions = vec![TraceException::new(kind, message, None)];
+    let mut i = 1;
+    while let Some(line) = lines.get(i) {
+        let t = line.trim();
+        let cause = [("Caused by: ", "caused by"), ("Suppressed: ", "suppressed")]
+            .into_iter()
+            .find_map(|(prefix, relation)| {
+                Some((exception_line(t.strip_prefix(prefix)?)?, relation))
+            });
+        if let Some(f) = t.strip_prefix("at ") {
+            let frame = jvm_or_node_frame(language, f, first + i);
+            exceptions.last_mut().unwrap().frames.push(frame);
+        } else if let Some(((kind, message), relation)) = cause {
+            exceptions.push(TraceException::new(kind, message, Some(relation)));
+        } else if !t.starts_with("... ") {
+            // `... 12 more` (frames shared with the enclosing trace) is skipped; anything else ends the trace.
+            break;
+        }
+        i += 1;
+    }
+    Some(Parsed {
+        language,
+        len: i,
+        exceptions,
+    })
+}
+
+/// Number of lines of the trace starting at `lines[0]`, if one starts there.
+pub fn trace_len(lines: &[&str]) -> Option<usize> {
+    parse(lines, 0).map(|p| p.len)
+}
+
+/// True if the whole text is a single stack trace.
+pub fn looks_like_stacktrace(content: &str) -> bool {
+    let lines: Vec<&str> = content.trim().lines().collect();
+    trace_len(&lines) == Some(lines.len())
+}
+
+/// Replace each run of two or more consecutive framework frames with its first frame.
+fn collapse_framework_frames(frames: Vec<TraceFrame>) -> Vec<TraceFrame> {
+    let mut out: Vec<TraceFrame> = Vec::new();
+    let mut run = 0;
+    for frame in frames {
+        if frame.framework && run > 0 {
+            run += 1;
+            let last = out.last_mut().unwrap();
+            last.collapsed = run;
+            continue;
+        }
+        run = u32::from(frame.framework);
+        out.push(frame);
+    }
+    out
+}
+
+/// Find the stack traces in a buffer and split them into exceptions and frames. Framework frames
+/// are collapsed unless `collapse_framework` is `false`.
+#[tauri::command]
+pub fn stack_traces(content: String, collapse_framework: Option<bool>) -> Vec<StackTrace> {
+    let lines: Vec<&str> = content.lines().collect();
+    let collapse = collapse_framework.unwrap_or(true);
+    let mut traces = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let Some(mut parsed) = parse(&lines[i..], i) else {
+            i += 1;
+            continue;
+        };
+        if collapse {
+            for e in &mut parsed.exceptions {
+                e.frames = collapse_framework_frames(std::mem::take(&mut e.frames));
+            }
+        }
+        traces.push(StackTrace {
+            language: parsed.language.name(),
+            start_line: i as u32 + 1,
+            end_line: (i + parsed.len) as u32,
+            exceptions: parsed.exceptions,
+        });
+        i += parsed.len;
+    }
+    traces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JAVA: &str = "Exception in thread \"main\" java.lang.IllegalStateException: boom\n\tat com.acme.Service.run(Service.java:42)\n\tat org.springframework.aop.Invoker.invoke(Invoker.java:10)\n\tat org.springframework.aop.Proxy.call(Proxy.java:20)\n\tat com.acme.Main.main(Main.java:7)\nCaused by: java.io.IOException: disk full\n\tat java.base/java.io.FileOutputStream.write(Native Method)\n\t... 4 more";
+
+    #[test]
+    fn java_trace_with_cause_and_collapsed_frames() {
+        let traces = stack_traces(format!("log line\n{JAVA}\nnext log line"), None);
+        assert_eq!(traces.len(), 1);
+        let t = &traces[0];
+        assert_eq!((t.language, t.start_line, t.end_line), ("java", 2, 9));
+        assert_eq!(t.exceptions[0].kind, "java.lang.IllegalStateException");
+        assert_eq!(t.exceptions[0].message, "boom");
+        let frames = &t.exceptions[0].frames;
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0].function, "com.acme.Service.run");
+        assert_eq!(frames[0].file.as_deref(), Some("Service.java"));
+        assert_eq!(frames[0].line, Some(42));
+        assert_eq!(frames[0].source_line, 3);
+        assert!(frames[1].framework);
+        assert_eq!(frames[1].collapsed, 2);
+        let cause = &t.exceptions[1];
+        assert_eq!(cause.relation.as_deref(), Some("caused by"));
+        assert_eq!(cause.kind, "java.io.IOException");
+        assert!(cause.frames[0].framework);
+        assert_eq!(cause.frames[0].file, None);
+    }
+
+    #[test]
+    fn python_chained_traceback_is_reported_outermost_first() {
+        let content = "Traceback (most recent call last):\n  File \"/app/db.py\", line 3, in load\n    return json.loads(raw)\nValueError: bad json\n\nThe above exception was the direct cause of the following exception:\n\nTraceback (most recent call last):\n  File \"/app/main.py\", line 9, in <module>\n    load()\nRuntimeError: config unreadable\nafter";
+        let traces = stack_traces(content.to_string(), Some(false));
+        assert_eq!(traces.len(), 1);
+        assert_eq!(traces[0].end_line, 11);
+        let e = &traces[0].exceptions;
+        assert_eq!(
+            (e[0].kind.as_str(), e[0].message.as_str()),
+            ("RuntimeError", "config unreadable")
+        );
+        assert_eq!(e[0].frames[0].function, "<module>");
+        assert_eq!(e[1].kind, "ValueError");
+        assert_eq!(e[1].relation.as_deref(), Some("caused by"));
+        assert_eq!(e[1].frames[0].file.as_deref(), Some("/app/db.py"));
+        assert_eq!(e[1].frames[0].line, Some(3));
+    }
+
+    #[test]
+    fn go_panic_frames_take_their_file_from_the_next_line() {
+        let content = "panic: runtime error: index out of range\n\ngoroutine 1 [running]:\nmain.(*Server).handle(0xc000010000, {0x1, 0x2})\n\t/app/server.go:31 +0x1d\nmain.main()\n\t/app/main.go:12 +0x25\nexit status 2";
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(trace_len(&lines), Some(7));
+        let traces = stack_traces(content.to_string(), None);
+        let panic = &traces[0].exceptions[0];
+        assert_eq!(panic.kind, "panic");
+        assert_eq!(panic.frames[0].function, "main.(*Server).handle");
+        assert_eq!(panic.frames[0].file.as_deref(), Some("/app/server.go"));
+        assert_eq!(panic.frames[1].line, Some(12));
+    }
+
+    #[test]
+    fn node_and_rust_traces() {
+        let node = "TypeError: Cannot read properties of undefined\n    at handler (/app/index.js:10:5)\n    at /app/node_modules/express/router.js:5:3\n    at process.processTicksAndRejections (node:internal/process/task_queues:95:5)";
+        assert!(looks_like_stacktrace(node));
+        let t = &stack_traces(node.to_string(), Some(false))[0];
+        assert_eq!(t.language, "node");
+        let frames = &t.exceptions[0].frames;
+        assert_eq!(
+            (frames[0].function.as_str(), frames[0].column),
+            ("handler", Some(5))
+        );
+        assert_eq!(frames[1].function, "");
+        assert!(frames[1].framework && frames[2].framework);
+
+        let rust = "thread 'main' panicked at src/main.rs:4:5:\nindex out of bounds\nstack backtrace:\n   0: rust_begin_unwind\n             at /rustc/abc/library/std/src/panicking.rs:645:5\n   1: app::parse::h0123456789abcdef\n             at ./src/main.rs:4:5\nnote: Some details are omitted.";
+        let t = &stack_traces(rust.to_string(), Some(false))[0];
+        assert_eq!(t.end_line, 8);
+        let panic = &t.exceptions[0];
+        assert_eq!(panic.message, "index out of bounds");
+        assert_eq!(panic.frames[1].function, "app::parse");
+        assert_eq!(panic.frames[1].line, Some(4));
+        assert!(panic.frames[0].framework && !panic.frames[1].framework);
+    }
+
+    #[test]
+    fn plain_text_is_not_a_trace() {
+        assert!(!looks_like_stacktrace(
+            "Error: something failed\nplease retry"
+        ));
+        assert!(!looks_like_stacktrace("panic: at the disco"));
+        assert!(stack_traces("hello\nworld".to_string(), None).is_empty());
+    }
+}
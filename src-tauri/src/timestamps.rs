@@ -0,0 +1,563 @@
+//! Timestamp recognition: epoch seconds/millis/micros/nanos, ISO 8601, RFC 2822 and common log
+//! formats, parsed to UTC instants and rewritten into a chosen timezone and format.
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{
+    DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone, Utc,
+};
+use chrono_tz::Tz;
+use serde::Serialize;
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// Epoch seconds accepted as timestamps: 2000-01-01 up to 2100-01-01.
+const EPOCH_RANGE: std::ops::Range<i64> = 946_684_800..4_102_444_800;
+
+/// A timestamp found in the content.
+#[derive(Debug, Serialize)]
+pub struct Timestamp {
+    /// 1-based line number.
+    pub line: usize,
+    /// Byte offset of the timestamp within the line.
+    pub start: usize,
+    /// Byte offset just past the timestamp.
+    pub end: usize,
+    pub text: String,
+    /// `iso8601`, `rfc2822`, `clf` (Apache/nginx access log), `datetime` (`2024/05/01 12:00:00`),
+    /// or `epoch_s`, `epoch_ms`, `epoch_us`, `epoch_ns`.
+    pub format: String,
+    /// The instant in UTC, RFC 3339 with milliseconds.
+    pub utc: String,
+    pub epoch_millis: i64,
+    /// False when the text carries no zone and the source timezone was assumed.
+    pub zoned: bool,
+}
+
+/// A timezone given by name: `UTC`, `local`, a fixed offset (`+02:00`) or an IANA name
+/// (`Europe/Paris`).
+#[derive(Clone, Copy)]
+enum Zone {
+    Fixed(FixedOffset),
+    Named(Tz),
+    Local,
+}
+
+impl Zone {
+    fn parse(name: &str) -> Result<Zone, String> {
+        let name = name.trim();
+        if name.eq_ignore_ascii_case("utc") || name == "Z" {
+            return Ok(Zone::Fixed(FixedOffset::east_opt(0).unwrap()));
+        }
+        if name.eq_ignore_ascii_case("local") {
+            return Ok(Zone::Local);
+        }
+        let mut cur = Cursor::new(name);
+        if let Some(offset) = cur.offset().filter(|_| cur.at_end()) {
+            return Ok(Zone::Fixed(offset));
+        }
+        name.parse::<Tz>()
+            .map(Zone::Named)
+            .map_err(|_| format!("Unknown timezone: {}", name))
+    }
+
+    fn instant_of(self, naive: &NaiveDateTime) -> Option<DateTime<Utc>> {
+        match self {
+            Zone::Fixed(offset) => offset
+                .from_local_datetime(naive)
+                .earliest()
+                .map(|d| d.to_utc()),
+            Zone::Named(tz) => tz.from_local_datetime(naive).earliest().map(|d| d.to_utc()),
+            Zone::Local => Local
+                .from_local_datetime(naive)
+                .earliest()
+                .map(|d| d.to_utc()),
+        }
+    }
+
+    fn local_of(self, instant: &DateTime<Utc>) -> DateTime<FixedOffset> {
+        match self {
+            Zone::Fixed(offset) => instant.with_timezone(&offset),
+            Zone::Named(tz) => instant.with_timezone(&tz).fixed_offset(),
+            Zone::Local => instant.with_timezone(&Local).fixed_offset(),
+        }
+    }
+}
+
+/// Byte cursor for the hand-written timestamp parsers.
+struct Cursor<'a> {
+    s: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(s: &'a str) -> Self {
+        Cursor {
+            s: s.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.s.len()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.s.get(self.pos).copied()
+    }
+
+    fn lit(&mut self, b: u8) -> bool {
+        let ok = self.peek() == Some(b);
+        if ok {
+            self.pos += 1;
+        }
+        ok
+    }
+
+    /// Between `min` and `max` ASCII digits.
+    fn digits(&mut self, min: usize, max: usize) -> Option<u32> {
+        let start = self.pos;
+        while self.pos - start < max && self.peek().is_some_and(|b| b.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if self.pos - start < min {
+            self.pos = start;
+            return None;
+        }
+        std::str::from_utf8(&self.s[start..self.pos])
+            .ok()?
+            .parse()
+            .ok()
+    }
+
+    /// One of `names` (case-sensitive); returns its index.
+    fn name(&mut self, names: &[&str]) -> Option<usize> {
+        let rest = &self.s[self.pos..];
+        let index = names.iter().position(|n| rest.starts_with(n.as_bytes()))?;
+        self.pos += names[index].len();
+        Some(index)
+    }
+
+    /// `+HH:MM`, `+HHMM` or `+HH`.
+    fn offset(&mut self) -> Option<FixedOffset> {
+        let start = self.pos;
+        let sign = match self.peek() {
+            Some(b'+') => 1,
+            Some(b'-') => -1,
+            _ => return None,
+        };
+        self.pos += 1;
+        let hours = self.digits(2, 2);
+        let colon = self.lit(b':');
+        let minutes = match self.digits(2, 2) {
+            Some(m) => Some(m),
+            None if colon => None,
+            None => Some(0),
+        };
+        match (hours, minutes) {
+            (Some(h), Some(m)) if h < 24 && m < 60 => {
+                FixedOffset::east_opt(sign * (h * 3600 + m * 60) as i32)
+            }
+            _ => {
+                self.pos = start;
+                None
+            }
+        }
+    }
+
+    /// `HH:MM:SS` with an optional `.fff`/`,fff` fraction; `HH:MM` when `seconds_optional`.
+    fn time(&mut self, seconds_optional: bool) -> Option<(u32, u32, u32, u32)> {
+        let h = self.digits(2, 2)?;
+        if !self.lit(b':') {
+            return None;
+        }
+        let m = self.digits(2, 2)?;
+        let s = if self.lit(b':') {
+            self.digits(2, 2)?
+        } else if seconds_optional {
+            0
+        } else {
+            return None;
+        };
+        let mut nanos = 0;
+        if matches!(self.peek(), Some(b'.' | b','))
+            && self.s.get(self.pos + 1).is_some_and(u8::is_ascii_digit)
+        {
+            self.pos += 1;
+            let start = self.pos;
+            let fraction = self.digits(1, 9)?;
+            nanos = fraction * 10u32.pow(9 - (self.pos - start) as u32);
+            while self.peek().is_some_and(|b| b.is_ascii_digit()) {
+                self.pos += 1;
+            }
+        }
+        Some((h, m, s, nanos))
+    }
+}
+
+fn naive(
+    y: u32,
+    mo: u32,
+    d: u32,
+    (h, mi, s, nanos): (u32, u32, u32, u32),
+) -> Option<NaiveDateTime> {
+    NaiveDate::from_ymd_opt(y as i32, mo, d)?.and_hms_nano_opt(h, mi, s, nanos)
+}
+
+/// A parsed timestamp: its length, local time and the offset it carries, if any.
+struct Parsed {
+    len: usize,
+    format: &'static str,
+    naive: NaiveDateTime,
+    offset: Option<FixedOffset>,
+}
+
+/// `2024-05-01T12:00:00.123Z`, `2024-05-01 12:00:00,123 +0200`, ...
+fn iso8601(s: &str) -> Option<Parsed> {
+    let mut c = Cursor::new(s);
+    let y = c.digits(4, 4)?;
+    c.lit(b'-').then_some(())?;
+    let mo = c.digits(2, 2)?;
+    c.lit(b'-').then_some(())?;
+    let d = c.digits(2, 2)?;
+    (c.lit(b'T') || c.lit(b' ')).then_some(())?;
+    let naive = naive(y, mo, d, c.time(false)?)?;
+    let before_zone = c.pos;
+    let offset = if c.lit(b'Z') {
+        FixedOffset::east_opt(0)
+    } else {
+        c.lit(b' ');
+        c.offset()
+    };
+    if offset.is_none() {
+        c.pos = before_zone;
+    }
+    Some(Parsed {
+        len: c.pos,
+        format: "iso8601",
+        naive,
+        offset,
+    })
+}
+
+/// `Wed, 01 May 2024 12:00:00 +0000` (weekday optional; zone as offset, `GMT`, `UT` or `UTC`).
+fn rfc2822(s: &str) -> Option<Parsed> {
+    let mut c = Cursor::new(s);
+    if c.name(&WEEKDAYS).is_some() {
+        (c.lit(b',') && c.lit(b' ')).then_some(())?;
+    }
+    let d = c.digits(1, 2)?;
+    c.lit(b' ').then_some(())?;
+    let mo = c.name(&MONTHS)? as u32 + 1;
+    c.lit(b' ').then_some(())?;
+    let y = c.digits(4, 4)?;
+    c.lit(b' ').then_some(())?;
+    let naive = naive(y, mo, d, c.time(true)?)?;
+    c.lit(b' ').then_some(())?;
+    let offset = match c.name(&["GMT", "UTC", "UT"]) {
+        Some(_) => FixedOffset::east_opt(0),
+        None => Some(c.offset()?),
+    };
+    Some(Parsed {
+        len: c.pos,
+        format: "rfc2822",
+        naive,
+        offset,
+    })
+}
+
+/// Apache/nginx access log: `01/May/2024:12:00:00 +0000`.
+fn clf(s: &str) -> Option<Parsed> {
+    let mut c = Cursor::new(s);
+    let d = c.digits(2, 2)?;
+    c.lit(b'/').then_some(())?;
+    let mo = c.name(&MONTHS)? as u32 + 1;
+    c.lit(b'/').then_some(())?;
+    let y = c.digits(4, 4)?;
+    c.lit(b':').then_some(())?;
+    let naive = naive(y, mo, d, c.time(false)?)?;
+    c.lit(b' ').then_some(())?;
+    let offset = Some(c.offset()?);
+    Some(Parsed {
+        len: c.pos,
+        format: "clf",
+        naive,
+        offset,
+    })
+}
+
+/// `2024/05/01 12:00:00`, as written by nginx error logs and Go's log package.
+fn slash_datetime(s: &str) -> Option<Parsed> {
+    let mut c = Cursor::new(s);
+    let y = c.digits(4, 4)?;
+    c.lit(b'/').then_some(())?;
+    let mo = c.digits(2, 2)?;
+    c.lit(b'/').then_some(())?;
+    let d = c.digits(2, 2)?;
+    c.lit(b' ').then_some(())?;
+    let naive = naive(y, mo, d, c.time(false)?)?;
+    Some(Parsed {
+        len: c.pos,
+        format: "datetime",
+        naive,
+        offset: None,
+    })
+}
+
+/// A run of 10, 13, 16 or 19 digits read as epoch seconds, millis, micros or nanos (seconds may
+/// carry a fraction), if it falls in [`EPOCH_RANGE`].
+fn epoch(s: &str) -> Option<(usize, &'static str, DateTime<Utc>)> {
+    let b = s.as_bytes();
+    let len = b.iter().take_while(|b| b.is_ascii_digit()).count();
+    let number: i64 = s[..len].parse().ok()?;
+    let (format, scale) = match len {
+        10 => ("epoch_s", 1),
+        13 => ("epoch_ms", 1_000),
+        16 => ("epoch_us", 1_000_000),
+        19 => ("epoch_ns", 1_000_000_000),
+        _ => return None,
+    };
+    let secs = number / scale;
+    if !EPOCH_RANGE.contains(&secs) {
+        return None;
+    }
+    let mut nanos = (number % scale) * (1_000_000_000 / scale);
+    let mut end = len;
+    if len == 10 && b.get(len) == Some(&b'.') {
+        let frac = b[len + 1..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        if (1..=9).contains(&frac) {
+            nanos = s[len + 1..len + 1 + frac].parse::<i64>().ok()? * 10i64.pow(9 - frac as u32);
+            end = len + 1 + frac;
+        }
+    }
+    if b.get(end)
+        .is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'.')
+    {
+        return None;
+    }
+    Some((end, format, DateTime::from_timestamp(secs, nanos as u32)?))
+}
+
+/// A timestamp found on a line: byte range, format, instant and whether it carried a zone.
+struct Found {
+    start: usize,
+    end: usize,
+    format: &'static str,
+    instant: DateTime<Utc>,
+    zoned: bool,
+}
+
+fn scan_line(line: &str, source: Zone) -> Vec<Found> {
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i < line.len() {
+        if !line.is_char_boundary(i) {
+            i += 1;
+            continue;
+        }
+        let prev = line[..i].chars().next_back();
+        let rest = &line[i..];
+        if prev.is_some_and(|c| c.is_alphanumeric() || c == '.')
+            || !rest.starts_with(|c: char| c.is_ascii_alphanumeric())
+        {
+            i += 1;
+            continue;
+        }
+        let parsed = [iso8601, clf, rfc2822, slash_datetime]
+            .iter()
+            .filter_map(|parse| parse(rest))
+            .filter(|p| {
+                !rest
+                    .as_bytes()
+                    .get(p.len)
+                    .is_some_and(u8::is_ascii_alphanumeric)
+            })
+            .find_map(|p| {
+                let instant = match p.offset {
+                    Some(offset) => offset.from_local_datetime(&p.naive).single()?.to_utc(),
+                    None => source.instant_of(&p.naive)?,
+                };
+                Some((p.len, p.format, instant, p.offset.is_some()))
+            })
+            .or_else(|| epoch(rest).map(|(len, format, instant)| (len, format, instant, true)));
+        match parsed {
+            Some((len, format, instant, zoned)) => {
+                found.push(Found {
+                    start: i,
+                    end: i + len,
+                    format,
+                    instant,
+                    zoned,
+                });
+                i += len;
+            }
+            None => i += 1,
+        }
+    }
+    found
+}
+
+/// Render an instant in `zone`: `rfc3339` (default), `rfc2822`, `epoch_s`, `epoch_ms`, or a
+/// strftime pattern such as `%Y-%m-%d %H:%M:%S`.
+fn render(instant: &DateTime<Utc>, zone: Zone, format: &str) -> String {
+    let local = zone.local_of(instant);
+    match format {
+        "rfc3339" => local.to_rfc3339_opts(SecondsFormat::Millis, true),
+        "rfc2822" => local.to_rfc2822(),
+        "epoch_s" => instant.timestamp().to_string(),
+        "epoch_ms" => instant.timestamp_millis().to_string(),
+        pattern => local.format(pattern).to_string(),
+    }
+}
+
+/// Find timestamps line by line, with their position and UTC instant. Timestamps without a zone
+/// are read in `source_timezone` (default UTC).
+#[tauri::command]
+pub fn find_timestamps(
+    content: String,
+    source_timezone: Option<String>,
+) -> Result<Vec<Timestamp>, String> {
+    let source = Zone::parse(source_timezone.as_deref().unwrap_or("UTC"))?;
+    let mut timestamps = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        for f in scan_line(line, source) {
+            timestamps.push(Timestamp {
+                line: index + 1,
+                start: f.start,
+                end: f.end,
+                text: line[f.start..f.end].to_string(),
+                format: f.format.to_string(),
+                utc: f.instant.to_rfc3339_opts(SecondsFormat::Millis, true),
+                epoch_millis: f.instant.timestamp_millis(),
+                zoned: f.zoned,
+            });
+        }
+    }
+    Ok(timestamps)
+}
+
+/// Rewrite every timestamp into `timezone` (`UTC`, `local`, `+02:00` or an IANA name) and `format`
+/// (see [`render`]); the rest of the content is left as is.
+#[tauri::command]
+pub fn convert_timestamps(
+    content: String,
+    timezone: String,
+    format: Option<String>,
+    source_timezone: Option<String>,
+) -> Result<String, String> {
+    let zone = Zone::parse(&timezone)?;
+    let source = Zone::parse(source_timezone.as_deref().unwrap_or("UTC"))?;
+    let format = format.unwrap_or_else(|| "rfc3339".to_string());
+    if StrftimeItems::new(&format).any(|item| item == Item::Error) {
+        return Err(format!("Invalid format: {}", format));
+    }
+    let mut out = String::with_capacity(content.len());
+    for line in content.split_inclusive('\n') {
+        let mut last = 0;
+        for f in scan_line(line, source) {
+            out.push_str(&line[last..f.start]);
+            out.push_str(&render(&f.instant, zone, &format));
+            last = f.end;
+        }
+        out.push_str(&line[last..]);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(line: &str) -> Vec<(String, String, String)> {
+        find_timestamps(line.to_string(), None)
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.text, t.format, t.utc))
+            .collect()
+    }
+
+    #[test]
+    fn find_timestamps_recognizes_each_style() {
+        let at = |text: &str, format: &str| {
+            (
+                text.to_string(),
+                format.to_string(),
+                "2024-05-01T10:00:00.000Z".to_string(),
+            )
+        };
+        assert_eq!(
+            found("ts=2024-05-01T12:00:00+02:00 ok"),
+            vec![at("2024-05-01T12:00:00+02:00", "iso8601")]
+        );
+        assert_eq!(
+            found("2024-05-01 10:00:00,000 INFO"),
+            vec![at("2024-05-01 10:00:00,000", "iso8601")]
+        );
+        assert_eq!(
+            found("Date: Wed, 01 May 2024 06:00:00 -0400"),
+            vec![at("Wed, 01 May 2024 06:00:00 -0400", "rfc2822")]
+        );
+        assert_eq!(
+            found("1.2.3.4 - - [01/May/2024:10:00:00 +0000] \"GET /\""),
+            vec![at("01/May/2024:10:00:00 +0000", "clf")]
+        );
+        assert_eq!(
+            found("2024/05/01 10:00:00 [error]"),
+            vec![at("2024/05/01 10:00:00", "datetime")]
+        );
+        assert_eq!(
+            found("{\"t\":1714557600,\"ms\":1714557600000}"),
+            vec![at("1714557600", "epoch_s"), at("1714557600000", "epoch_ms")]
+        );
+        assert_eq!(
+            found("ns 1714557600000000000"),
+            vec![at("1714557600000000000", "epoch_ns")]
+        );
+    }
+
+    #[test]
+    fn find_timestamps_ignores_other_numbers_and_uses_source_zone() {
+        assert!(found("id 12345 v1.1714557600 order 9999999999999 ip 10.0.0.1").is_empty());
+        let t = find_timestamps(
+            "2024-05-01 12:00:00".to_string(),
+            Some("Europe/Paris".to_string()),
+        )
+        .unwrap();
+        assert_eq!(
+            (t[0].utc.as_str(), t[0].zoned),
+            ("2024-05-01T10:00:00.000Z", false)
+        );
+        assert!(find_timestamps(String::new(), Some("Mars/Olympus".to_string())).is_err());
+    }
+
+    #[test]
+    fn convert_timestamps_rewrites_into_zone_and_format() {
+        let log = "a 2024-05-01T10:00:00Z start\r\nb 1714557660123 done\n";
+        let out = convert_timestamps(log.to_string(), "America/New_York".to_string(), None, None)
+            .unwrap();
+        assert_eq!(
+            out,
+            "a 2024-05-01T06:00:00.000-04:00 start\r\nb 2024-05-01T06:01:00.123-04:00 done\n"
+        );
+        let out = convert_timestamps(
+            log.to_string(),
+            "+05:30".to_string(),
+            Some("%H:%M:%S".to_string()),
+            None,
+        )
+        .unwrap();
+        assert_eq!(out, "a 15:30:00 start\r\nb 15:31:00 done\n");
+        assert!(convert_timestamps(
+            log.to_string(),
+            "UTC".to_string(),
+            Some("%Q".to_string()),
+            None
+        )
+        .is_err());
+    }
+}
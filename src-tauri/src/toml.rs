@@ -0,0 +1,219 @@
+//! TOML: detection, comment-preserving formatting and validation.
+
+use toml_edit::{DocumentMut, Item, Table, Value};
+
+use crate::{FormatOptions, ParseError};
+
+fn parse(content: &str) -> Result<DocumentMut, ParseError> {
+    content.parse::<DocumentMut>().map_err(|e| {
+        let offset = e.span().map(|s| s.start).unwrap_or(0);
+        ParseError::at_offset(content, offset, e.message())
+    })
+}
+
+/// True if the content parses as TOML and uses TOML's own idioms: a `[table]` header,
+/// or a `key = value` line whose value is a string, array or inline table.
+/// Plain `KEY=value` lines are left to the properties detector.
+pub fn looks_like_toml(content: &str) -> bool {
+    let trimmed = content.trim();
+    if !(trimmed.contains('=') || trimmed.starts_with('[')) || parse(trimmed).is_err() {
+        return false;
+    }
+    trimmed.lines().map(str::trim).any(|l| {
+        l.starts_with('[')
+            || l.split_once(" = ").is_some_and(|(_, v)| {
+                v.trim_start().starts_with(['"', '\'', '[', '{'])
+            })
+    })
+}
+
+/// Track bracket depth and open multi-line strings across a fragment of a line.
+/// Stops at a comment; single-line strings are skipped so brackets inside them don't count.
+fn scan(text: &str, depth: &mut usize, multiline: &mut Option<&'static [u8]>) {
+    let b = text.as_bytes();
+    let mut i = 0;
+    while i < b.len() {
+        if let Some(delim) = *multiline {
+            if b[i..].starts_with(delim) {
+                *multiline = None;
+                i += delim.len();
+            } else {
+                i += if delim == b"\"\"\"" && b[i] == b'\\' { 2 } else { 1 };
+            }
+            continue;
+        }
+        match b[i] {
+            b'#' => break,
+            b'"' | b'\'' => {
+                let triple: &'static [u8] = if b[i] == b'"' { b"\"\"\"" } else { b"'''" };
+                if b[i..].starts_with(triple) {
+                    *multiline = Some(triple);
+                    i += 3;
+                    continue;
+                }
+                let quote = b[i];
+                i += 1;
+                while i < b.len() && b[i] != quote {
+                    i += if quote == b'"' && b[i] == b'\\' { 2 } else { 1 };
+                }
+            }
+            b'[' | b'{' => *depth += 1,
+            b']' | b'}' => *depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        i += 1;
+    }
+}
+
+/// Byte index of the `=` separating key and value, ignoring `=` inside quoted keys.
+fn key_value_split(line: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '=') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Line-based normalization: trim lines, `key = value` spacing, one blank line between blocks,
+/// a blank line before table headers, multi-line arrays indented by `indent`. Comments and value
+/// text are kept verbatim.
+fn normalize(content: &str, indent: &str) -> String {
+    let mut out: Vec<String> = Vec::new();
+    let mut depth = 0usize;
+    let mut multiline: Option<&'static [u8]> = None;
+    for line in content.lines() {
+        if multiline.is_some() {
+            out.push(line.to_string());
+            scan(line, &mut depth, &mut multiline);
+            continue;
+        }
+        let t = line.trim();
+        let last_blank = out.last().is_none_or(|l| l.is_empty());
+        if t.is_empty() {
+            if !last_blank {
+                out.push(String::new());
+            }
+            continue;
+        }
+        if depth > 0 {
+            let level = if t.starts_with([']', '}']) { depth - 1 } else { depth };
+            out.push(format!("{}{}", indent.repeat(level), t));
+            scan(t, &mut depth, &mut multiline);
+        } else if t.starts_with('[') {
+            let after_comment = out.last().is_some_and(|l| l.starts_with('#'));
+            if !last_blank && !after_comment {
+                out.push(String::new());
+            }
+            out.push(t.to_string());
+        } else if t.starts_with('#') {
+            out.push(t.to_string());
+        } else if let Some(eq) = key_value_split(t) {
+            let (key, value) = (t[..eq].trim(), t[eq + 1..].trim());
+            out.push(format!("{} = {}", key, value));
+            scan(value, &mut depth, &mut multiline);
+        } else {
+            out.push(t.to_string());
+        }
+    }
+    while out.last().is_some_and(|l| l.is_empty()) {
+        out.pop();
+    }
+    out.join("\n")
+}
+
+fn value_to_json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::String(s) => s.value().clone().into(),
+        Value::Integer(i) => (*i.value()).into(),
+        Value::Float(f) => f.value().to_string().into(),
+        Value::Boolean(b) => (*b.value()).into(),
+        Value::Datetime(d) => d.value().to_string().into(),
+        Value::Array(a) => a.iter().map(value_to_json).collect(),
+        Value::InlineTable(t) => t.iter().map(|(k, v)| (k.to_string(), value_to_json(v))).collect(),
+    }
+}
+
+fn table_to_json(table: &Table) -> serde_json::Value {
+    table.iter().map(|(k, v)| (k.to_string(), item_to_json(v))).collect()
+}
+
+/// Plain data view of a TOML item, used to check that formatting did not change meaning.
+fn item_to_json(item: &Item) -> serde_json::Value {
+    match item {
+        Item::None => serde_json::Value::Null,
+        Item::Value(v) => value_to_json(v),
+        Item::Table(t) => table_to_json(t),
+        Item::ArrayOfTables(a) => a.iter().map(table_to_json).collect(),
+    }
+}
+
+/// Normalize TOML layout, indenting continuation lines by `indent`, while keeping comments,
+/// key order and value spelling.
+pub fn format_document(content: &str, indent: &str) -> Result<String, ParseError> {
+    let original = parse(content)?;
+    let formatted = normalize(content, indent);
+    // Never hand back something that means something else; fall back to the input instead.
+    match parse(&formatted) {
+        Ok(doc) if item_to_json(doc.as_item()) == item_to_json(original.as_item()) => Ok(formatted),
+        _ => Ok(content.to_string()),
+    }
+}
+
+/// Normalize TOML layout while keeping comments, key order and value spelling. Honours the
+/// indent, tab and trailing-newline options (four spaces by default).
+/// Returns the parse error (with line/column) if the input is not valid TOML.
+#[tauri::command]
+pub fn format_toml(content: String, options: Option<FormatOptions>) -> Result<String, ParseError> {
+    let options = options.unwrap_or_default();
+    Ok(options.finish(format_document(&content, &options.indent_unit(4))?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn looks_like_toml_needs_toml_idioms() {
+        assert!(looks_like_toml("[package]\nname = \"siftview\"\nversion = \"0.1.0\""));
+        assert!(looks_like_toml("name = \"x\"\ntags = [\"a\"]"));
+        assert!(!looks_like_toml("PORT=8080\nDEBUG=true"));
+        assert!(!looks_like_toml("DB_HOST=localhost"));
+        assert!(!looks_like_toml("[db]\nhost=localhost"));
+    }
+
+    #[test]
+    fn format_toml_normalizes_and_keeps_comments() {
+        let raw = "# top\n  name=\"x\"   # trailing\n[deps]\nserde={ version = \"1\" }\n\n\n\nlist=[\n1,\n  2,\n]\ntext=\"\"\"\n  keep  me\n\"\"\"\n";
+        let out = format_toml(raw.to_string(), None).unwrap();
+        assert_eq!(
+            out,
+            "# top\nname = \"x\"   # trailing\n\n[deps]\nserde = { version = \"1\" }\n\nlist = [\n    1,\n    2,\n]\ntext = \"\"\"\n  keep  me\n\"\"\""
+        );
+    }
+
+    #[test]
+    fn format_toml_honours_indent_options() {
+        let options = FormatOptions { use_tabs: true, trailing_newline: true, ..Default::default() };
+        let out = format_toml("list=[\n1,\n]".to_string(), Some(options)).unwrap();
+        assert_eq!(out, "list = [\n\t1,\n]\n");
+    }
+
+    #[test]
+    fn format_toml_keeps_equals_inside_quoted_keys() {
+        let out = format_toml("\"a=b\"=1".to_string(), None).unwrap();
+        assert_eq!(out, "\"a=b\" = 1");
+    }
+
+    #[test]
+    fn format_toml_reports_line_and_column() {
+        let err = format_toml("[a]\nx = 1\ny = \n".to_string(), None).unwrap_err();
+        assert_eq!(err.line, 3);
+        assert_eq!(err.column, 5);
+        assert!(!err.message.is_empty());
+    }
+}
@@ -0,0 +1,556 @@
+//! YAML streams: `---`-separated documents as segments labelled by their Kubernetes-style
+//! `kind`/`metadata.name`, per-document formatting and a document outline.
+
+use serde_yaml::Value;
+
+use crate::{OutlineItem, Segment};
+
+/// `---` on its own or followed by content (`--- !tag`, `--- # comment`).
+fn is_document_start(line: &str) -> bool {
+    line == "---" || line.starts_with("--- ") || line.starts_with("---\t")
+}
+
+fn is_document_end(line: &str) -> bool {
+    line == "..." || line.starts_with("... ")
+}
+
+/// True for lines that carry no data: blank lines, comments and document markers.
+fn is_trivia(line: &str) -> bool {
+    let t = line.trim();
+    t.is_empty() || t.starts_with('#') || t == "---" || t == "..."
+}
+
+/// Line ranges (0-based, end exclusive) of the documents of a stream. Each document includes its
+/// `---` and `...` markers but not the blank lines around it.
+fn document_ranges(lines: &[&str]) -> Vec<(usize, usize)> {
+    let mut bounds = Vec::new();
+    let mut start = 0;
+    for (i, line) in lines.iter().enumerate() {
+        if is_document_start(line) && i > start {
+            bounds.push((start, i));
+            start = i;
+        } else if is_document_end(line) {
+            bounds.push((start, i + 1));
+            start = i + 1;
+        }
+    }
+    bounds.push((start, lines.len()));
+    bounds
+        .into_iter()
+        .filter_map(|(mut start, mut end)| {
+            while start < end && lines[start].trim().is_empty() {
+                start += 1;
+            }
+            while end > start && lines[end - 1].trim().is_empty() {
+                end -= 1;
+            }
+            (start < end).then_some((start, end))
+        })
+        .collect()
+}
+
+/// `Deployment/web` from a document's `kind` and `metadata.name`, or whichever of them it has.
+fn label(text: &str) -> Option<String> {
+    let value: Value = serde_yaml::from_str(text).ok()?;
+    let kind = value.get("kind").and_then(Value::as_str);
+    let name = value
+        .get("metadata")
+        .and_then(|m| m.get("name"))
+        .and_then(Value::as_str);
+    match (kind, name) {
+        (Some(kind), Some(name)) => Some(format!("{}/{}", kind, name)),
+        (Some(label), None) | (None, Some(label)) => Some(label.to_string()),
+        (None, None) => None,
+    }
+}
+
+/// True if the content is a stream of two or more YAML documents holding mappings or sequences.
+pub fn is_yaml_stream(content: &str) -> bool {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut collections = 0;
+    for (start, end) in document_ranges(&lines) {
+        match serde_yaml::from_str::<Value>(&lines[start..end].join("\n")) {
+            Ok(Value::Mapping(_) | Value::Sequence(_)) => collections += 1,
+            Ok(Value::Null) => {}
+            _ => return false,
+        }
+    }
+    collections > 1
+}
+
+/// One `yaml` segment per document, labelled with its `kind`/`metadata.name`.
+pub fn document_segments(content: &str) -> Vec<Segment> {
+    let lines: Vec<&str> = content.lines().collect();
+    let segments: Vec<Segment> = document_ranges(&lines)
+        .into_iter()
+        .map(|(start, end)| Segment {
+            start_line: start as u32 + 1,
+            end_line: end as u32,
+            kind: "yaml".to_string(),
+            label: label(&lines[start..end].join("\n")),
+            dialect: None,
+        })
+        .collect();
+    if segments.is_empty() {
+        return vec![Segment {
+            start_line: 1,
+            end_line: lines.len().max(1) as u32,
+            kind: "yaml".to_string(),
+            label: None,
+            dialect: None,
+        }];
+    }
+    segments
+}
+
+/// Width of a `- ` sequence entry indicator, after which its content starts.
+const DASH: usize = 2;
+
+/// Whether a scan has stopped inside a flow collection or a quoted scalar, which then continues on
+/// the next line.
+#[derive(Debug, Default)]
+struct Flow {
+    depth: usize,
+    quote: Option<u8>,
+}
+
+impl Flow {
+    fn inside(&self) -> bool {
+        self.depth > 0 || self.quote.is_some()
+    }
+}
+
+/// Scan a line (or the rest of one) from `flow`, returning the byte offsets of an inline comment
+/// and of the first `key: value` colon outside quotes and flow collections.
+fn scan(text: &str, flow: &mut Flow) -> (Option<usize>, Option<usize>) {
+    let b = text.as_bytes();
+    let mut colon = None;
+    let mut i = 0;
+    while i < b.len() {
+        let c = b[i];
+        let starts_node = i == 0 || matches!(b[i - 1], b' ' | b'\t' | b'[' | b'{' | b',');
+        match flow.quote {
+            Some(b'"') if c == b'\\' => i += 1,
+            Some(b'\'') if c == b'\'' && b.get(i + 1) == Some(&b'\'') => i += 1,
+            Some(q) if c == q => flow.quote = None,
+            Some(_) => {}
+            None => match c {
+                b'#' if i == 0 || matches!(b[i - 1], b' ' | b'\t') => return (Some(i), colon),
+                b'"' | b'\'' if starts_node => flow.quote = Some(c),
+                b'[' | b'{' if starts_node => flow.depth += 1,
+                b']' | b'}' if flow.depth > 0 => flow.depth -= 1,
+                b':' if flow.depth == 0
+                    && colon.is_none()
+                    && b.get(i + 1).is_none_or(|n| matches!(n, b' ' | b'\t')) =>
+                {
+                    colon = Some(i)
+                }
+                _ => {}
+            },
+        }
+        i += 1;
+    }
+    (None, colon)
+}
+
+/// `|`, `>-`, `|2+`, ...: the header of a block scalar.
+fn is_block_indicator(token: &str) -> bool {
+    token.len() <= 3
+        && token.starts_with(['|', '>'])
+        && token[1..]
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '+' || c == '-')
+}
+
+/// A content line with single spaces after `-` and `:` and before an inline comment.
+struct Normalized {
+    text: String,
+    /// Number of leading `- ` sequence indicators.
+    dashes: usize,
+    /// Offset in the original line (after indentation) of the node after the dashes.
+    content: usize,
+    /// Offset in the original line of the key, if the line is a `key: value` entry.
+    key: Option<usize>,
+    /// Whether the line's value is a block scalar header (`key: |`).
+    block_scalar: bool,
+}
+
+fn normalize(rest: &str, flow: &mut Flow) -> Normalized {
+    let (comment, colon) = scan(rest, flow);
+    let code = rest[..comment.unwrap_or(rest.len())].trim_end();
+    let mut pos = 0;
+    let mut dashes = 0;
+    while code[pos..].starts_with('-')
+        && code[pos + 1..]
+            .chars()
+            .next()
+            .is_none_or(|c| c == ' ' || c == '\t')
+    {
+        pos += 1;
+        pos += code[pos..].len() - code[pos..].trim_start().len();
+        dashes += 1;
+    }
+    let colon = colon.filter(|&c| c >= pos && c < code.len());
+    let (body, value) = match colon {
+        Some(c) => {
+            let value = code[c + 1..].trim();
+            let key = code[pos..c].trim_end();
+            if value.is_empty() {
+                (format!("{}:", key), value)
+            } else {
+                (format!("{}: {}", key, value), value)
+            }
+        }
+        None => (code[pos..].to_string(), &code[pos..]),
+    };
+    let mut text = format!("{}{}", "- ".repeat(dashes), body)
+        .trim_end()
+        .to_string();
+    if let Some(c) = comment {
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(&rest[c..]);
+    }
+    Normalized {
+        text,
+        dashes,
+        content: pos,
+        key: colon.map(|_| pos),
+        block_scalar: value
+            .split_whitespace()
+            .last()
+            .is_some_and(is_block_indicator),
+    }
+}
+
+/// New column for an original indentation, from the stack of (original, new) columns of the
+/// enclosing nodes and the width of one level.
+fn column(stack: &[(usize, usize)], indent: usize, step: usize) -> usize {
+    for &(orig, new) in stack.iter().rev() {
+        if orig == indent {
+            return new;
+        }
+        if orig < indent {
+            return new + step;
+        }
+    }
+    0
+}
+
+fn shifted(line: &str, indent: usize, shift: isize) -> String {
+    let indent_to = (indent as isize + shift).max(0) as usize;
+    format!("{}{}", " ".repeat(indent_to), &line[indent..])
+}
+
+/// Re-indent one document by `step` spaces per nesting level and normalize spacing; comments,
+/// anchors, tags, flow collections and quoted and block scalars are kept as written.
+fn format_document(doc: &[&str], step: usize) -> String {
+    let mut out: Vec<String> = Vec::new();
+    let mut stack: Vec<(usize, usize)> = vec![(0, 0)];
+    let mut flow = Flow::default();
+    // Lines continuing a flow collection, quoted scalar or block scalar move with their first line.
+    let mut shift: isize = 0;
+    let mut block_parent: Option<usize> = None;
+    for line in doc {
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        let rest = line[indent..].trim_end();
+        if let Some(parent) = block_parent {
+            if rest.is_empty() {
+                out.push(String::new());
+                continue;
+            }
+            if indent > parent {
+                out.push(shifted(line, indent, shift));
+                continue;
+            }
+            block_parent = None;
+        }
+        if flow.inside() {
+            scan(rest, &mut flow);
+            out.push(shifted(line, indent, shift).trim_end().to_string());
+            continue;
+        }
+        if rest.is_empty() {
+            out.push(String::new());
+        } else if rest.starts_with('#') {
+            out.push(format!(
+                "{}{}",
+                " ".repeat(column(&stack, indent, step)),
+                rest
+            ));
+        } else if indent == 0
+            && (is_document_start(rest) || is_document_end(rest) || rest.starts_with('%'))
+        {
+            stack.truncate(1);
+            out.push(rest.to_string());
+        } else {
+            while stack.len() > 1 && stack[stack.len() - 1].0 > indent {
+                stack.pop();
+            }
+            let new = column(&stack, indent, step);
+            if indent > stack[stack.len() - 1].0 {
+                stack.push((indent, new));
+            }
+            let line = normalize(rest, &mut flow);
+            let content = (indent + line.content, new + DASH * line.dashes);
+            if line.dashes > 0 && line.content < rest.len() {
+                stack.push(content);
+            }
+            shift = new as isize - indent as isize;
+            if line.block_scalar {
+                let (orig, new) = if line.key.is_some() {
+                    content
+                } else {
+                    (indent, new)
+                };
+                shift = new as isize - orig as isize;
+                block_parent = Some(orig);
+            }
+            out.push(format!("{}{}", " ".repeat(new), line.text));
+        }
+    }
+    out.join("\n")
+}
+
+/// Format each document of a YAML stream: `indent` spaces per nesting level, single spaces after
+/// `-` and `:` and before inline comments. Comments, anchors and aliases, key order and scalar
+/// styles are kept, and a document is only rewritten if it still parses to the same data.
+pub fn format_yaml(content: &str, indent: usize) -> Result<String, String> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut out = Vec::new();
+    for (start, end) in document_ranges(&lines) {
+        let doc = &lines[start..end];
+        let before: Value = serde_yaml::from_str(&doc.join("\n")).map_err(|e| e.to_string())?;
+        let formatted = format_document(doc, indent.max(1));
+        let after: Value = serde_yaml::from_str(&formatted).map_err(|e| e.to_string())?;
+        if before != after {
+            return Err("Formatting would change the document's data.".to_string());
+        }
+        out.push(formatted);
+    }
+    Ok(out.join("\n"))
+}
+
+/// A value in flow style on one line: `{key: value, list: [1, 2]}`. Strings stay plain where
+/// YAML allows it and are double-quoted otherwise.
+fn flow(value: &Value) -> String {
+    match value {
+        Value::Sequence(items) => {
+            let items: Vec<String> = items.iter().map(flow).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Mapping(map) => {
+            let entries: Vec<String> = map
+                .iter()
+                .map(|(key, value)| format!("{}: {}", flow(key), flow(value)))
+                .collect();
+            format!("{{{}}}", entries.join(", "))
+        }
+        Value::Tagged(tagged) => format!("{} {}", tagged.tag, flow(&tagged.value)),
+        Value::String(s) => {
+            let plain = serde_yaml::to_string(value).unwrap_or_default();
+            if plain.trim_end() == s && !s.contains([',', '[', ']', '{', '}']) {
+                s.clone()
+            } else {
+                serde_json::to_string(s).unwrap_or_default()
+            }
+        }
+        _ => serde_yaml::to_string(value)
+            .unwrap_or_default()
+            .trim_end()
+            .to_string(),
+    }
+}
+
+/// Write each document of a YAML stream in flow style on one line, documents separated by `---`.
+/// Comments, anchors and scalar styles are dropped; a document that wouldn't parse back to the
+/// same data is an error.
+pub fn flow_yaml(content: &str) -> Result<String, String> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut out = Vec::new();
+    for (start, end) in document_ranges(&lines) {
+        let value: Value =
+            serde_yaml::from_str(&lines[start..end].join("\n")).map_err(|e| e.to_string())?;
+        let flowed = flow(&value);
+        let after: Value = serde_yaml::from_str(&flowed).map_err(|e| e.to_string())?;
+        if after != value {
+            return Err("Flow style would change the document's data.".to_string());
+        }
+        out.push(flowed);
+    }
+    Ok(out.join("\n---\n"))
+}
+
+/// Documents of a YAML stream (titled by `kind`/`metadata.name`, else `Document N`) with their
+/// top-level keys beneath them.
+#[tauri::command]
+pub fn yaml_outline(content: String) -> Vec<OutlineItem> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut items = Vec::new();
+    let documents = document_ranges(&lines)
+        .into_iter()
+        .filter(|(start, end)| !lines[*start..*end].iter().all(|l| is_trivia(l)));
+    for (n, (start, end)) in documents.enumerate() {
+        items.push(OutlineItem {
+            level: 1,
+            title: label(&lines[start..end].join("\n"))
+                .unwrap_or_else(|| format!("Document {}", n + 1)),
+            start_line: start as u32 + 1,
+            end_line: end as u32,
+        });
+        let keys: Vec<(usize, &str)> = (start..end)
+            .filter(|&i| !lines[i].starts_with([' ', '\t', '#', '-']) && !is_trivia(lines[i]))
+            .filter_map(|i| Some((i, lines[i].split_once(':')?.0.trim())))
+            .collect();
+        for (k, &(line, key)) in keys.iter().enumerate() {
+            let mut key_end = keys.get(k + 1).map_or(end, |&(next, _)| next);
+            while key_end > line + 1 && is_trivia(lines[key_end - 1]) {
+                key_end -= 1;
+            }
+            items.push(OutlineItem {
+                level: 2,
+                title: key.trim_matches(['"', '\'']).to_string(),
+                start_line: line as u32 + 1,
+                end_line: key_end as u32,
+            });
+        }
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> String {
+        std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../test-files/sample-manifests.yaml"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn document_segments_label_each_manifest() {
+        let content = sample();
+        assert!(is_yaml_stream(&content));
+        assert!(!is_yaml_stream("a: 1\n"));
+        assert!(!is_yaml_stream("notes\n---\nmore notes\n---\n"));
+        let segments: Vec<(u32, u32, Option<String>)> = document_segments(&content)
+            .into_iter()
+            .map(|s| (s.start_line, s.end_line, s.label))
+            .collect();
+        assert_eq!(
+            segments,
+            vec![
+                (1, 5, Some("Namespace/staging".to_string())),
+                (6, 22, Some("Deployment/web".to_string())),
+                (23, 31, Some("Service/web".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn format_yaml_keeps_comments_anchors_and_styles() {
+        let content = "\
+# Service config
+defaults: &defaults   # shared
+    timeout:   30
+    retries : 3
+service:
+    <<: *defaults
+    name:    'api'      # quoted stays quoted
+    hosts:
+    -   \"a.example\"
+    -   b.example
+    ports: [80,   443]
+    script: |
+        echo \"hi\"
+          indented
+    env:
+        -   name: A
+            value: \"1\"
+";
+        assert_eq!(
+            format_yaml(content, 2).unwrap(),
+            "\
+# Service config
+defaults: &defaults # shared
+  timeout: 30
+  retries: 3
+service:
+  <<: *defaults
+  name: 'api' # quoted stays quoted
+  hosts:
+  - \"a.example\"
+  - b.example
+  ports: [80,   443]
+  script: |
+      echo \"hi\"
+        indented
+  env:
+    - name: A
+      value: \"1\""
+        );
+    }
+
+    #[test]
+    fn format_yaml_formats_each_document() {
+        let out = format_yaml(
+            "# header\n---\na:\n    b: {c: 1}\n...\n\nc:\n  - 1\n---\n# empty\n",
+            2,
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            "# header\n---\na:\n  b: {c: 1}\n...\nc:\n  - 1\n---\n# empty"
+        );
+        assert_eq!(format_yaml("x:   1\n", 2).unwrap(), "x: 1");
+        assert_eq!(
+            format_yaml("a:\n  b:\n  - c: 1\n    d: 2\n", 4).unwrap(),
+            "a:\n    b:\n    - c: 1\n      d: 2"
+        );
+        assert!(format_yaml("a: 1\n---\nb: [\n", 2).is_err());
+    }
+
+    #[test]
+    fn flow_yaml_writes_each_document_on_one_line() {
+        let out = flow_yaml(&sample()).unwrap();
+        let docs: Vec<&str> = out.split("\n---\n").collect();
+        assert_eq!(docs.len(), 3);
+        assert_eq!(
+            docs[0],
+            "{apiVersion: v1, kind: Namespace, metadata: {name: staging}}"
+        );
+        assert!(docs[1].contains("ports: [{containerPort: 80}]"));
+        assert!(docs[1].contains("image: nginx:1.27"));
+        let out = flow_yaml("note: |\n  a, b\n  c\nid: '007'\n").unwrap();
+        assert_eq!(out, "{note: \"a, b\\nc\\n\", id: \"007\"}");
+    }
+
+    #[test]
+    fn yaml_outline_lists_documents_and_top_level_keys() {
+        let outline = yaml_outline(sample());
+        let summary: Vec<(u8, &str, u32, u32)> = outline
+            .iter()
+            .map(|o| (o.level, o.title.as_str(), o.start_line, o.end_line))
+            .collect();
+        assert_eq!(
+            summary[..6],
+            [
+                (1, "Namespace/staging", 1, 5),
+                (2, "apiVersion", 2, 2),
+                (2, "kind", 3, 3),
+                (2, "metadata", 4, 5),
+                (1, "Deployment/web", 6, 22),
+                (2, "apiVersion", 7, 7),
+            ]
+        );
+        assert_eq!(summary[8], (2, "spec", 12, 22));
+        assert_eq!(
+            yaml_outline("---\nitems: []\n".to_string())[0].title,
+            "Document 1"
+        );
+    }
+}
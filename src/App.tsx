@@ -39,6 +39,10 @@ export interface Segment {
   start_line: number;
   end_line: number;
   kind: string;
+  /** Short description, e.g. `Deployment/web` for a YAML document. */
+  label?: string;
+  /** Sniffed dialect of a `csv` segment; passed back unchanged when formatting. */
+  dialect?: { delimiter: string; quote: string; backslash_escape: boolean; has_header: boolean };
 }
 
 export interface Tab {
@@ -335,7 +339,7 @@ export default function App() {
       const id = nextTabId();
       const extension = getExtension(path);
       const [detected, segments] = await Promise.all([
-        invoke<DetectedType>("detect_content", { content, extension: extension || undefined }),
+        invoke<DetectedType>("detect_content", { content, extension: extension || undefined, path }),
         invoke<Segment[]>("detect_segments", { content, extension: extension || undefined }),
       ]);
       const newTab: Tab = {
@@ -64,10 +64,59 @@ function webWriteFile(path: string, content: string): Promise<void> {
   return Promise.resolve();
 }
 
+/** Extension → kind, mirroring `kind_from_extension` in the Rust backend. */
+const EXTENSION_KINDS: Record<string, string> = {
+  json: "json",
+  jsonl: "ndjson",
+  ndjson: "ndjson",
+  csv: "csv",
+  tsv: "csv",
+  tab: "csv",
+  xml: "xml",
+  html: "html",
+  htm: "html",
+  yaml: "yaml",
+  yml: "yaml",
+  env: "properties",
+  properties: "properties",
+  toml: "toml",
+  ini: "ini",
+  cfg: "ini",
+  md: "markdown",
+  markdown: "markdown",
+  sql: "sql",
+  http: "http",
+  eml: "email",
+  db: "sqlite",
+  sqlite: "sqlite",
+  sqlite3: "sqlite",
+  parquet: "parquet",
+  arrow: "arrow",
+  arrows: "arrow",
+  feather: "arrow",
+  ipc: "arrow",
+  xlsx: "spreadsheet",
+  xlsm: "spreadsheet",
+  xlsb: "spreadsheet",
+  xls: "spreadsheet",
+  ods: "spreadsheet",
+  msgpack: "msgpack",
+  mpk: "msgpack",
+  cbor: "cbor",
+  bson: "bson",
+  pb: "protobuf",
+  binpb: "protobuf",
+  protobuf: "protobuf",
+  pem: "pem",
+  crt: "pem",
+  cer: "pem",
+  csr: "pem",
+  key: "pem",
+};
+
 function webDetectContent(content: string, extension: string): { kind: string; confidence: number } {
-  const ext = extension.toLowerCase();
-  if (["json", "csv", "xml", "html", "yaml", "yml", "env", "properties"].includes(ext)) {
-    const kind = ext === "yml" ? "yaml" : ext === "env" || ext === "properties" ? "properties" : ext;
+  const kind = EXTENSION_KINDS[extension.toLowerCase()];
+  if (kind) {
     return { kind, confidence: 0.95 };
   }
   const t = content.trim();
@@ -79,7 +128,7 @@ function webDetectContent(content: string, extension: string): { kind: string; c
 function webDetectSegments(content: string, extension: string): { start_line: number; end_line: number; kind: string }[] {
   const lines = content.split("\n");
   if (lines.length === 0) {
-    const kind = extension ? (extension === "yml" ? "yaml" : extension === "env" || extension === "properties" ? "properties" : extension) : "text";
+    const kind = extension ? EXTENSION_KINDS[extension.toLowerCase()] ?? extension : "text";
     return [{ start_line: 1, end_line: 1, kind }];
   }
   const segments: { start_line: number; end_line: number; kind: string }[] = [];
@@ -0,0 +1,7 @@
+-- Active users with their order totals
+select u.id, u.name, sum(o.total) as total from users u left join orders o on o.user_id = u.id where u.active = true and o.created_at between '2024-01-01' and '2024-12-31' group by u.id, u.name order by total desc limit 20;
+
+update users set last_seen = now(), visits = visits + 1 where id in (select user_id from sessions where expires_at > now());
+
+-- Postgres-style dollar quoting is kept as written
+create function touch() returns trigger as $$ begin new.updated_at = now(); return new; end; $$ language plpgsql;